@@ -1,4 +1,6 @@
-CREATE TABLE removal (
+-- smoelius: `IF NOT EXISTS` allows databases created before migrations were introduced to be
+-- upgraded in place.
+CREATE TABLE IF NOT EXISTS removal (
     span    TEXT NOT NULL,
     text    TEXT NOT NULL,
     outcome TEXT NOT NULL CHECK (outcome IN ('nonbuildable', 'failed', 'timed-out', 'passed')),
@@ -0,0 +1,10 @@
+ALTER TABLE removal DROP COLUMN run_id;
+ALTER TABLE removal DROP COLUMN necessist_version;
+ALTER TABLE removal DROP COLUMN signal;
+ALTER TABLE removal DROP COLUMN exit_code;
+ALTER TABLE removal DROP COLUMN wall_time;
+ALTER TABLE removal DROP COLUMN ended_at;
+ALTER TABLE removal DROP COLUMN started_at;
+ALTER TABLE removal DROP COLUMN kind;
+ALTER TABLE removal DROP COLUMN framework;
+ALTER TABLE removal DROP COLUMN test_name;
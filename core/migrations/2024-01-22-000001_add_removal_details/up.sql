@@ -0,0 +1,10 @@
+ALTER TABLE removal ADD COLUMN test_name TEXT;
+ALTER TABLE removal ADD COLUMN framework TEXT;
+ALTER TABLE removal ADD COLUMN kind TEXT CHECK (kind IN ('macro', 'method-call', 'statement'));
+ALTER TABLE removal ADD COLUMN started_at BIGINT;
+ALTER TABLE removal ADD COLUMN ended_at BIGINT;
+ALTER TABLE removal ADD COLUMN wall_time DOUBLE;
+ALTER TABLE removal ADD COLUMN exit_code INTEGER;
+ALTER TABLE removal ADD COLUMN signal INTEGER;
+ALTER TABLE removal ADD COLUMN necessist_version TEXT;
+ALTER TABLE removal ADD COLUMN run_id TEXT;
@@ -0,0 +1,28 @@
+-- smoelius: Only the most recent run's removals can be kept.
+CREATE TABLE removal_old (
+    span              TEXT NOT NULL,
+    text              TEXT NOT NULL,
+    outcome           TEXT NOT NULL CHECK (outcome IN ('nonbuildable', 'failed', 'timed-out', 'passed')),
+    url               TEXT NOT NULL,
+    test_name         TEXT,
+    framework         TEXT,
+    kind              TEXT CHECK (kind IN ('macro', 'method-call', 'statement')),
+    started_at        BIGINT,
+    ended_at          BIGINT,
+    wall_time         DOUBLE,
+    exit_code         INTEGER,
+    signal            INTEGER,
+    necessist_version TEXT,
+    run_id            TEXT,
+    PRIMARY KEY (span)
+);
+
+INSERT INTO removal_old
+SELECT * FROM removal
+WHERE run_id = (SELECT id FROM run ORDER BY timestamp DESC LIMIT 1);
+
+DROP TABLE removal;
+
+ALTER TABLE removal_old RENAME TO removal;
+
+DROP TABLE run;
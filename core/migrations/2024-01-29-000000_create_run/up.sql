@@ -0,0 +1,38 @@
+CREATE TABLE run (
+    id                TEXT NOT NULL,
+    git_commit        TEXT,
+    timestamp         BIGINT NOT NULL,
+    necessist_version TEXT,
+    PRIMARY KEY (id)
+);
+
+-- smoelius: Removals recorded before runs were tracked are assigned to a single `legacy` run.
+UPDATE removal SET run_id = 'legacy' WHERE run_id IS NULL;
+
+INSERT INTO run (id, timestamp, necessist_version)
+SELECT run_id, COALESCE(MIN(started_at), 0), MAX(necessist_version) FROM removal GROUP BY run_id;
+
+-- smoelius: SQLite cannot alter a table's primary key, so the table is recreated.
+CREATE TABLE removal_new (
+    span              TEXT NOT NULL,
+    text              TEXT NOT NULL,
+    outcome           TEXT NOT NULL CHECK (outcome IN ('nonbuildable', 'failed', 'timed-out', 'passed')),
+    url               TEXT NOT NULL,
+    test_name         TEXT,
+    framework         TEXT,
+    kind              TEXT CHECK (kind IN ('macro', 'method-call', 'statement')),
+    started_at        BIGINT,
+    ended_at          BIGINT,
+    wall_time         DOUBLE,
+    exit_code         INTEGER,
+    signal            INTEGER,
+    necessist_version TEXT,
+    run_id            TEXT NOT NULL REFERENCES run (id),
+    PRIMARY KEY (run_id, span)
+);
+
+INSERT INTO removal_new SELECT * FROM removal;
+
+DROP TABLE removal;
+
+ALTER TABLE removal_new RENAME TO removal;
@@ -0,0 +1 @@
+ALTER TABLE removal DROP COLUMN fingerprint;
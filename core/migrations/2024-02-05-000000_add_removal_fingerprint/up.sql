@@ -0,0 +1 @@
+ALTER TABLE removal ADD COLUMN fingerprint TEXT;
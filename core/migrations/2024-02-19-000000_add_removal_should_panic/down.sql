@@ -0,0 +1 @@
+ALTER TABLE removal DROP COLUMN should_panic;
@@ -28,9 +28,20 @@ impl Backup {
         })
     }
 
+    pub fn path(&self) -> Option<&Path> {
+        self.tempfile.as_ref().map(NamedTempFile::path)
+    }
+
     pub fn disable(&mut self) -> Result<()> {
         self.tempfile.take().map_or(Ok(()), NamedTempFile::close)
     }
+
+    /// Keeps the backup on disk without restoring the backed-up file.
+    pub fn keep(&mut self) -> Result<()> {
+        self.tempfile.take().map_or(Ok(()), |tempfile| {
+            tempfile.keep().map(|_| ()).map_err(Into::into)
+        })
+    }
 }
 
 impl Drop for Backup {
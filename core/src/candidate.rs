@@ -0,0 +1,83 @@
+use crate::Span;
+use anyhow::{anyhow, Error, Result};
+use heck::ToKebabCase;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+/// A span that could be removed, along with the test in which it appears
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Candidate {
+    pub span: Span,
+    pub test_name: String,
+    pub kind: Kind,
+    /// Whether the test is expected to panic, e.g., because it has Rust's `#[should_panic]`
+    /// attribute. Such a test passes when it panics, so a removal that causes it to panic is
+    /// reported as passed.
+    pub should_panic: bool,
+}
+
+impl Candidate {
+    /// Returns a value that identifies the candidate independent of where it appears in its file.
+    /// The value is derived from the candidate's whitespace-normalized text, the name of the test
+    /// in which it appears, and the lines immediately before and after it. `lines` are the lines of
+    /// the candidate's file, which callers split once per file rather than once per candidate.
+    pub(crate) fn fingerprint(&self, lines: &[&str]) -> Result<String> {
+        let text = self.span.source_text()?;
+        let before = self
+            .span
+            .start
+            .line
+            .checked_sub(2)
+            .and_then(|index| lines.get(index))
+            .copied()
+            .unwrap_or_default();
+        let after = lines.get(self.span.end.line).copied().unwrap_or_default();
+
+        let mut hash = FNV_OFFSET_BASIS;
+        for component in [&text, self.test_name.as_str(), before, after] {
+            for byte in normalize(component).bytes().chain(std::iter::once(0)) {
+                hash ^= u64::from(byte);
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        Ok(format!("{hash:016x}"))
+    }
+}
+
+// smoelius: FNV-1a is used rather than `DefaultHasher` because fingerprints are stored in
+// necessist.db, and `DefaultHasher`'s output is not guaranteed to be stable across Rust versions.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+fn normalize(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[remain::sorted]
+pub enum Kind {
+    /// A statement consisting of a macro call
+    Macro,
+    /// A method call without its receiver
+    MethodCall,
+    /// Any other statement
+    Statement,
+}
+
+impl std::fmt::Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format!("{self:?}").to_kebab_case())
+    }
+}
+
+impl FromStr for Kind {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Kind::iter()
+            .find(|kind| kind.to_string() == s)
+            .ok_or_else(|| anyhow!("Unknown kind `{}`", s))
+    }
+}
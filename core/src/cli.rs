@@ -1,10 +1,14 @@
-use crate::{framework, Necessist, Warning};
+use crate::{framework, Kind, Necessist, Sample, Shard, ShardBy, Storage, TimeBudget, Warning};
 use clap::{crate_version, ArgAction, Parser, ValueEnum};
 use std::path::PathBuf;
 
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Parser)]
-#[clap(version = crate_version!())]
+#[clap(
+    version = crate_version!(),
+    args_conflicts_with_subcommands = true,
+    disable_help_subcommand = true
+)]
 #[remain::sorted]
 pub struct Opts<Identifier: Clone + Send + Sync + ValueEnum + 'static> {
     #[clap(
@@ -28,24 +32,83 @@ pub struct Opts<Identifier: Clone + Send + Sync + ValueEnum + 'static> {
         help = "Treat <WARNING> as an error; `--deny all` treats all warnings as errors"
     )]
     deny: Vec<Warning>,
-    #[clap(long, help = "Dump sqlite database contents to the console")]
+    #[clap(long, help = "Dump the most recent run in the database to the console")]
     dump: bool,
     #[clap(long, help = "Dump removal candidates and exit (for debugging)")]
     dump_candidates: bool,
     #[clap(long, help = "Assume testing framework is <FRAMEWORK>")]
     framework: Option<framework::Auto<Identifier>>,
+    #[clap(
+        long,
+        action = ArgAction::Append,
+        hide_possible_values = true,
+        help = "Try only candidates of kind <KIND> (`statement`, `method-call`, or `macro`); may \
+                be passed more than once"
+    )]
+    kind: Vec<Kind>,
     #[clap(long, help = "Do not perform dry runs")]
     no_dry_run: bool,
-    #[clap(long, help = "Do not output to an sqlite database")]
+    #[clap(long, help = "Do not output to a database")]
     no_sqlite: bool,
     #[clap(long, help = "Do not output to the console")]
     quiet: bool,
-    #[clap(long, help = "Discard sqlite database contents")]
+    #[clap(long, help = "Start a new run; earlier runs are kept in the database")]
     reset: bool,
-    #[clap(long, help = "Resume from the sqlite database")]
+    #[clap(long, help = "Resume from the database")]
     resume: bool,
-    #[clap(long, help = "Root directory of the project under test")]
+    #[clap(long, global = true, help = "Root directory of the project under test")]
     root: Option<String>,
+    #[clap(
+        long,
+        value_name = "N|P%",
+        help = "Try only a random sample of N candidates (or P percent of them)"
+    )]
+    sample: Option<Sample>,
+    #[clap(
+        long,
+        requires = "sample",
+        help = "Seed the random choice of --sample; a recorded run's seed is reused by --resume"
+    )]
+    seed: Option<u64>,
+    #[clap(
+        long,
+        value_name = "I/N",
+        help = "Try only the I-th of N disjoint parts of the candidates, e.g., `--shard 1/4`"
+    )]
+    shard: Option<Shard>,
+    #[clap(
+        long,
+        requires = "shard",
+        help = "Divide the candidates among shards by <SHARD_BY>; `file` is the default"
+    )]
+    shard_by: Option<ShardBy>,
+    #[clap(
+        long,
+        global = true,
+        help = "Store results in <STORAGE>; `sqlite` is the default"
+    )]
+    storage: Option<Storage>,
+    #[clap(subcommand)]
+    subcommand: Option<Subcommand>,
+    #[clap(
+        long,
+        help = "Print summary statistics at the end of the run (or of --dump)"
+    )]
+    summary: bool,
+    #[clap(
+        long,
+        action = ArgAction::Append,
+        value_name = "PATTERN",
+        help = "Try only candidates in tests whose names match <PATTERN>, in which `*` matches any \
+                string; may be passed more than once"
+    )]
+    test: Vec<String>,
+    #[clap(
+        long,
+        value_name = "DURATION",
+        help = "Stop cleanly once <DURATION> (e.g., `30m` or `1h30m`) has elapsed"
+    )]
+    time_budget: Option<TimeBudget>,
     #[clap(
         long,
         help = "Maximum number of seconds to run any test; 60 is the default, 0 means no timeout"
@@ -53,7 +116,16 @@ pub struct Opts<Identifier: Clone + Send + Sync + ValueEnum + 'static> {
     timeout: Option<u64>,
     #[clap(long, help = "Show test outcomes besides `passed`")]
     verbose: bool,
-    #[clap(value_name = "TEST_FILES", help = "Test files to mutilate (optional)")]
+    #[clap(
+        long,
+        help = "After the run, re-check the candidates of test files as they are edited"
+    )]
+    watch: bool,
+    #[clap(
+        value_name = "TEST_FILES",
+        help = "Test files to mutilate (optional); `path:line` or `path:line-line` selects only \
+                the candidates that overlap those lines"
+    )]
     ztest_files: Vec<String>,
     #[clap(
         last = true,
@@ -63,6 +135,55 @@ pub struct Opts<Identifier: Clone + Send + Sync + ValueEnum + 'static> {
     zzargs: Vec<String>,
 }
 
+#[derive(Debug, clap::Subcommand)]
+enum Subcommand {
+    #[clap(about = "Report how the removals of two runs recorded in the database differ")]
+    Compare {
+        #[clap(help = "Id (or unique id prefix) of the earlier run")]
+        run_a: String,
+        #[clap(help = "Id (or unique id prefix) of the later run")]
+        run_b: String,
+    },
+    #[clap(
+        about = "Combine the most recent runs of several databases into one run in a new database"
+    )]
+    Merge {
+        #[clap(required = true, help = "Databases to merge, e.g., one per shard")]
+        inputs: Vec<String>,
+        #[clap(short, long, help = "Database to create")]
+        output: String,
+    },
+    #[clap(about = "Restore files left modified by an interrupted run")]
+    Restore,
+    #[clap(
+        about = "Report tests at least THRESHOLD of whose removals passed, ranked, with the \
+                 passed removals"
+    )]
+    Vacuous {
+        #[clap(
+            long,
+            help = "Report tests at least <THRESHOLD> of whose removals passed; 1.0 is the default"
+        )]
+        threshold: Option<f64>,
+        #[clap(help = "Id (or unique id prefix) of the run; the most recent run is the default")]
+        run: Option<String>,
+    },
+}
+
+impl From<Subcommand> for crate::Subcommand {
+    fn from(subcommand: Subcommand) -> Self {
+        match subcommand {
+            Subcommand::Compare { run_a, run_b } => Self::Compare { run_a, run_b },
+            Subcommand::Merge { inputs, output } => Self::Merge {
+                inputs: inputs.iter().map(PathBuf::from).collect(),
+                output: PathBuf::from(output),
+            },
+            Subcommand::Restore => Self::Restore,
+            Subcommand::Vacuous { threshold, run } => Self::Vacuous { threshold, run },
+        }
+    }
+}
+
 impl<Identifier: Clone + Send + Sync + ValueEnum> From<Opts<Identifier>>
     for (Necessist, framework::Auto<Identifier>)
 {
@@ -74,19 +195,31 @@ impl<Identifier: Clone + Send + Sync + ValueEnum> From<Opts<Identifier>>
             dump,
             dump_candidates,
             framework,
+            kind,
             no_dry_run,
             no_sqlite,
             quiet,
             reset,
             resume,
             root,
+            sample,
+            seed,
+            shard,
+            shard_by,
+            storage,
+            subcommand,
+            summary,
+            test,
+            time_budget,
             timeout,
             verbose,
+            watch,
             ztest_files,
             zzargs,
         } = opts;
         let framework = framework.unwrap_or_default();
         let root = root.map(PathBuf::from);
+        let subcommand = subcommand.map(Into::into);
         let test_files = ztest_files.iter().map(PathBuf::from).collect::<Vec<_>>();
         let args = zzargs;
         (
@@ -96,14 +229,25 @@ impl<Identifier: Clone + Send + Sync + ValueEnum> From<Opts<Identifier>>
                 deny,
                 dump,
                 dump_candidates,
+                kind,
                 no_dry_run,
                 no_sqlite,
                 quiet,
                 reset,
                 resume,
                 root,
+                sample,
+                seed,
+                shard,
+                shard_by,
+                storage,
+                subcommand,
+                summary,
+                test,
+                time_budget,
                 timeout,
                 verbose,
+                watch,
                 test_files,
                 args,
             },
@@ -0,0 +1,264 @@
+use crate::{span, store, LightContext, Outcome};
+use anyhow::Result;
+use std::collections::{BTreeMap, VecDeque};
+
+type Key = (String, proc_macro2::LineColumn, proc_macro2::LineColumn);
+
+struct Entry {
+    span: String,
+    text: String,
+    outcome: Outcome,
+    fingerprint: Option<String>,
+}
+
+/// Reports how the removals of the run identified by `run_b` differ from those of the run
+/// identified by `run_a`.
+pub(crate) fn compare(context: &LightContext, run_a: &str, run_b: &str) -> Result<()> {
+    let (run_id_a, removals_a) = store::load_run(context, run_a)?;
+    let (run_id_b, removals_b) = store::load_run(context, run_b)?;
+
+    let entries_a = index(removals_a)?;
+    let entries_b = index(removals_b)?;
+
+    let mut newly_passing = Vec::new();
+    let mut no_longer_passing = Vec::new();
+    let mut appeared = Vec::new();
+    let mut disappeared = Vec::new();
+
+    // smoelius: A removal of run b whose span matches none of run a's is matched by fingerprint,
+    // as in `relocate_past_removals`, so that a candidate that merely moved (e.g., because of an
+    // edit above it) is compared rather than reported as having disappeared and appeared.
+    let mut unmatched_a = BTreeMap::<_, VecDeque<_>>::new();
+    for (key, entry_a) in &entries_a {
+        if entries_b.contains_key(key) {
+            continue;
+        }
+        if let Some(fingerprint) = &entry_a.fingerprint {
+            unmatched_a
+                .entry((&key.0, fingerprint))
+                .or_default()
+                .push_back(key);
+        }
+    }
+
+    let mut relocated_a = Vec::new();
+
+    for (key, entry_b) in &entries_b {
+        let entry_a = entries_a.get(key).or_else(|| {
+            let fingerprint = entry_b.fingerprint.as_ref()?;
+            let key_a = unmatched_a
+                .get_mut(&(&key.0, fingerprint))
+                .and_then(VecDeque::pop_front)?;
+            relocated_a.push(key_a);
+            entries_a.get(key_a)
+        });
+        let Some(entry_a) = entry_a else {
+            appeared.push(describe(entry_b));
+            continue;
+        };
+        let change = format!(
+            "{}: `{}` ({} -> {})",
+            entry_b.span, entry_b.text, entry_a.outcome, entry_b.outcome
+        );
+        match (entry_a.outcome, entry_b.outcome) {
+            (Outcome::Passed, Outcome::Passed) => {}
+            (_, Outcome::Passed) => newly_passing.push(change),
+            (Outcome::Passed, _) => no_longer_passing.push(change),
+            (_, _) => {}
+        }
+    }
+
+    for (key, entry_a) in &entries_a {
+        if !entries_b.contains_key(key) && !relocated_a.contains(&key) {
+            disappeared.push(describe(entry_a));
+        }
+    }
+
+    (context.println)(&format!("Comparing run {run_id_a} to run {run_id_b}"));
+
+    report(
+        context,
+        "Removals that newly pass (possible test regressions)",
+        &newly_passing,
+    );
+    report(
+        context,
+        "Removals that no longer pass (possible test fixes)",
+        &no_longer_passing,
+    );
+    report(context, "Candidates that appeared", &appeared);
+    report(context, "Candidates that disappeared", &disappeared);
+
+    if newly_passing.is_empty()
+        && no_longer_passing.is_empty()
+        && appeared.is_empty()
+        && disappeared.is_empty()
+    {
+        (context.println)(&"No differences");
+    }
+
+    Ok(())
+}
+
+fn index(removals: Vec<(String, String, Outcome, Option<String>)>) -> Result<BTreeMap<Key, Entry>> {
+    removals
+        .into_iter()
+        .map(|(span, text, outcome, fingerprint)| {
+            let key = span::parse_parts(&span)?;
+            Ok((
+                key,
+                Entry {
+                    span,
+                    text,
+                    outcome,
+                    fingerprint,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn describe(entry: &Entry) -> String {
+    format!("{}: `{}` ({})", entry.span, entry.text, entry.outcome)
+}
+
+fn report(context: &LightContext, heading: &str, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+
+    (context.println)(&format!("{heading}:"));
+
+    for line in lines {
+        (context.println)(&format!("    {line}"));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::compare;
+    use crate::{
+        jsonl::Jsonl,
+        store::{
+            self,
+            test::{removal, run, with_context},
+            Backend, Removal,
+        },
+        Outcome, Storage,
+    };
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    #[test]
+    fn runs_are_compared() {
+        let tempdir = tempdir().unwrap();
+
+        let mut backend = Jsonl::open(&tempdir.path().join("necessist.jsonl")).unwrap();
+
+        for (run_id, removals) in [
+            (
+                "a",
+                [(1, "passed"), (2, "failed"), (3, "passed"), (4, "failed")],
+            ),
+            (
+                "b",
+                [
+                    (1, "passed"),
+                    (2, "passed"),
+                    (3, "failed"),
+                    (5, "timed-out"),
+                ],
+            ),
+        ] {
+            backend.insert_run(&run(run_id)).unwrap();
+            for (line, outcome) in removals {
+                backend
+                    .insert_removal(&removal(run_id, line, outcome))
+                    .unwrap();
+            }
+        }
+
+        // smoelius: A candidate that moved from line 6 to line 7, e.g., because a line was inserted
+        // above it, is matched by its fingerprint.
+        for (run_id, line, outcome) in [("a", 6, "passed"), ("b", 7, "failed")] {
+            backend
+                .insert_removal(&Removal {
+                    fingerprint: Some("moved".to_owned()),
+                    ..removal(run_id, line, outcome)
+                })
+                .unwrap();
+        }
+
+        assert_eq!(
+            [
+                "Comparing run a to run b",
+                "Removals that newly pass (possible test regressions):",
+                "    src/lib.rs:2:5-2:12: `n += 1;` (failed -> passed)",
+                "Removals that no longer pass (possible test fixes):",
+                "    src/lib.rs:3:5-3:12: `n += 1;` (passed -> failed)",
+                "    src/lib.rs:7:5-7:12: `n += 1;` (passed -> failed)",
+                "Candidates that appeared:",
+                "    src/lib.rs:5:5-5:12: `n += 1;` (timed-out)",
+                "Candidates that disappeared:",
+                "    src/lib.rs:4:5-4:12: `n += 1;` (failed)",
+            ]
+            .as_slice(),
+            compare_output(tempdir.path(), Storage::Jsonl, "a", "b")
+        );
+    }
+
+    // smoelius: Removals recorded before runs were tracked belong to the `legacy` run.
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn database_predating_runs_can_be_compared() {
+        use crate::sqlite::MIGRATIONS;
+        use diesel::{sql_query, Connection, RunQueryDsl, SqliteConnection};
+        use diesel_migrations::MigrationHarness;
+
+        let tempdir = tempdir().unwrap();
+
+        let necessist_db = tempdir.path().join("necessist.db");
+
+        {
+            let mut connection =
+                SqliteConnection::establish(&necessist_db.to_string_lossy()).unwrap();
+
+            // smoelius: Apply just the migrations that precede `create_run`.
+            for _ in 0..2 {
+                connection.run_next_migration(MIGRATIONS).unwrap();
+            }
+
+            sql_query(
+                "INSERT INTO removal (span, text, outcome, url, started_at) VALUES \
+                 ('src/lib.rs:1:5-1:12', 'n += 1;', 'passed', '', 0)",
+            )
+            .execute(&mut connection)
+            .unwrap();
+        }
+
+        assert_eq!(
+            ["Comparing run legacy to run legacy", "No differences"].as_slice(),
+            compare_output(tempdir.path(), Storage::Sqlite, "legacy", "legacy")
+        );
+
+        with_context(tempdir.path(), Storage::Sqlite, |context| {
+            let (run_id, removals) = store::load_run(context, "leg").unwrap();
+            assert_eq!("legacy", run_id);
+            assert_eq!(
+                vec![(
+                    "src/lib.rs:1:5-1:12".to_owned(),
+                    "n += 1;".to_owned(),
+                    Outcome::Passed,
+                    None
+                )],
+                removals
+            );
+        });
+    }
+
+    fn compare_output(root: &Path, storage: Storage, run_a: &str, run_b: &str) -> Vec<String> {
+        with_context(root, storage, |context| {
+            compare(context, run_a, run_b).unwrap();
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use crate::LightContext;
+use crate::{LightContext, Storage};
 use anyhow::{bail, Result};
 use regex::Regex;
 use std::{collections::BTreeMap, fs::read_to_string, path::Path};
@@ -11,12 +11,112 @@ pub enum IgnoredPathDisambiguation {
     Method,
 }
 
+/// How the Rust framework runs tests
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[remain::sorted]
+pub enum RustRunner {
+    #[default]
+    CargoTest,
+    Nextest,
+}
+
+/// Cargo settings for the Rust framework, i.e., necessist.toml's `[rust]` table. The settings in
+/// `[rust.package.<name>]` apply only to the package `<name>`, and take precedence over the others.
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Rust {
+    #[serde(default)]
+    pub all_features: Option<bool>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub no_default_features: Option<bool>,
+    #[serde(default)]
+    pub package: BTreeMap<String, RustPackage>,
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// Cargo settings for one package, i.e., necessist.toml's `[rust.package.<name>]` table
+#[derive(Clone, Debug, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct RustPackage {
+    #[serde(default)]
+    pub all_features: Option<bool>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub no_default_features: Option<bool>,
+    #[serde(default)]
+    pub profile: Option<String>,
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+impl Rust {
+    /// Returns the flags to pass to cargo when building or running the tests of the package
+    /// `name`. A package's `features` are added to the global ones.
+    #[must_use]
+    pub fn cargo_flags(&self, name: &str) -> Vec<String> {
+        let package = self.package.get(name);
+
+        let all_features = package
+            .and_then(|package| package.all_features)
+            .or(self.all_features)
+            .unwrap_or_default();
+        let no_default_features = package
+            .and_then(|package| package.no_default_features)
+            .or(self.no_default_features)
+            .unwrap_or_default();
+        let features = self
+            .features
+            .iter()
+            .chain(
+                package
+                    .map(|package| &package.features)
+                    .into_iter()
+                    .flatten(),
+            )
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let profile = package
+            .and_then(|package| package.profile.as_ref())
+            .or(self.profile.as_ref());
+        let target = package
+            .and_then(|package| package.target.as_ref())
+            .or(self.target.as_ref());
+
+        let mut flags = Vec::new();
+        if all_features {
+            flags.push("--all-features".to_owned());
+        }
+        if no_default_features {
+            flags.push("--no-default-features".to_owned());
+        }
+        if !features.is_empty() {
+            flags.extend(["--features".to_owned(), features.join(",")]);
+        }
+        if let Some(profile) = profile {
+            flags.extend(["--profile".to_owned(), profile.clone()]);
+        }
+        if let Some(target) = target {
+            flags.extend(["--target".to_owned(), target.clone()]);
+        }
+        flags
+    }
+}
+
 pub struct Compiled {
     ignored_functions: Vec<Regex>,
     ignored_macros: Vec<Regex>,
     ignored_methods: Vec<Regex>,
     ignored_path_disambiguation: IgnoredPathDisambiguation,
     ignored_tests: Vec<String>,
+    test_attributes: Vec<String>,
+    transparent_methods: Vec<String>,
 }
 
 impl Compiled {
@@ -40,6 +140,14 @@ impl Compiled {
     pub fn is_ignored_test(&self, name: &str) -> bool {
         self.ignored_tests.iter().any(|s| name == s)
     }
+    #[must_use]
+    pub fn is_test_attribute(&self, path: &str) -> bool {
+        self.test_attributes.iter().any(|s| path == s)
+    }
+    #[must_use]
+    pub fn is_transparent_method(&self, name: &str) -> bool {
+        self.transparent_methods.iter().any(|s| name == s)
+    }
 }
 
 #[derive(Default, serde::Deserialize, serde::Serialize)]
@@ -54,6 +162,24 @@ pub struct Toml {
     pub ignored_path_disambiguation: Option<IgnoredPathDisambiguation>,
     #[serde(default)]
     pub ignored_tests: Vec<String>,
+    #[serde(default)]
+    pub rust: Option<Rust>,
+    #[serde(default)]
+    pub rust_doctests: Option<bool>,
+    #[serde(default)]
+    pub rust_include_ignored: Option<bool>,
+    #[serde(default)]
+    pub rust_runner: Option<RustRunner>,
+    #[serde(default)]
+    pub storage: Option<Storage>,
+    #[serde(default)]
+    pub test_attributes: Vec<String>,
+    #[serde(default)]
+    pub transparent_methods: Vec<String>,
+    #[serde(default)]
+    pub url_remote: Option<String>,
+    #[serde(default)]
+    pub url_template: Option<String>,
     #[serde(flatten)]
     pub other: BTreeMap<String, toml::Value>,
 }
@@ -87,6 +213,15 @@ impl Toml {
             ignored_methods,
             ignored_path_disambiguation,
             ignored_tests,
+            rust,
+            rust_doctests,
+            rust_include_ignored,
+            rust_runner,
+            storage,
+            test_attributes,
+            transparent_methods,
+            url_remote,
+            url_template,
             other: _,
         } = other;
 
@@ -106,6 +241,39 @@ impl Toml {
 
         self.ignored_tests.extend_from_slice(ignored_tests);
 
+        if rust.is_some() {
+            self.rust.clone_from(rust);
+        }
+
+        if rust_doctests.is_some() {
+            self.rust_doctests = *rust_doctests;
+        }
+
+        if rust_include_ignored.is_some() {
+            self.rust_include_ignored = *rust_include_ignored;
+        }
+
+        if rust_runner.is_some() {
+            self.rust_runner = *rust_runner;
+        }
+
+        if storage.is_some() {
+            self.storage = *storage;
+        }
+
+        self.test_attributes.extend_from_slice(test_attributes);
+
+        self.transparent_methods
+            .extend_from_slice(transparent_methods);
+
+        if url_remote.is_some() {
+            self.url_remote.clone_from(url_remote);
+        }
+
+        if url_template.is_some() {
+            self.url_template.clone_from(url_template);
+        }
+
         Some(self)
     }
 
@@ -116,6 +284,15 @@ impl Toml {
             ignored_methods,
             ignored_path_disambiguation,
             ignored_tests,
+            rust: _,
+            rust_doctests: _,
+            rust_include_ignored: _,
+            rust_runner: _,
+            storage: _,
+            test_attributes,
+            transparent_methods,
+            url_remote: _,
+            url_template: _,
             other: _,
         } = self;
 
@@ -129,6 +306,8 @@ impl Toml {
             ignored_methods,
             ignored_path_disambiguation: ignored_path_disambiguation.unwrap_or_default(),
             ignored_tests,
+            test_attributes,
+            transparent_methods,
         })
     }
 }
@@ -158,12 +337,14 @@ fn escape(pattern: &str) -> Result<String> {
             s.push(ch);
         } else if ch == '.' {
             s.push_str("\\.");
+        } else if ch == ':' {
+            s.push(ch);
         } else if ch == '*' {
             s.push_str(".*");
         } else {
             bail!(
-                "Patterns can contain only letters, numbers, '.', '_', or `*`, which does not \
-                 include '{}'",
+                "Patterns can contain only letters, numbers, '.', ':', '_', or `*`, which does \
+                 not include '{}'",
                 ch
             );
         }
@@ -206,6 +387,7 @@ fn patterns() {
             &[],
         ),
         ("*.Equal", &["assert.Equal"], &["Equal"]),
+        ("std::*", &["std::assert_eq"], &["assert_eq"]),
     ];
 
     for (pattern, positive, negative) in EXAMPLES {
@@ -218,3 +400,37 @@ fn patterns() {
         }
     }
 }
+
+#[test]
+fn rust_cargo_flags() {
+    let toml: Toml = toml::from_str(
+        r#"
+[rust]
+features = ["a"]
+profile = "release"
+
+[rust.package.foo]
+features = ["b"]
+no-default-features = true
+profile = "dev"
+"#,
+    )
+    .unwrap();
+    let rust = toml.rust.unwrap();
+
+    assert_eq!(
+        ["--features", "a", "--profile", "release"].as_slice(),
+        rust.cargo_flags("bar")
+    );
+    assert_eq!(
+        [
+            "--no-default-features",
+            "--features",
+            "a,b",
+            "--profile",
+            "dev"
+        ]
+        .as_slice(),
+        rust.cargo_flags("foo")
+    );
+}
@@ -1,8 +1,9 @@
 use crate::{
-    config,
+    compare, config, filter,
     framework::{self, Applicable, ToImplementation},
-    note, source_warn, sqlite, util, warn, Outcome, SourceFile, Span, ToConsoleString, WarnFlags,
-    Warning,
+    journal, note, sample, shard, source_warn, store, summary, time_budget, util, vacuous, warn,
+    watch, Candidate, Kind, Observer, Outcome, Sample, Session, Shard, ShardBy, SourceFile, Span,
+    Storage, TimeBudget, ToConsoleString, WarnFlags, Warning,
 };
 use ansi_term::Style;
 use anyhow::{anyhow, bail, ensure, Context as _, Result};
@@ -14,21 +15,31 @@ use once_cell::sync::{Lazy, OnceCell};
 use rlimit::{getrlimit, setrlimit, Resource};
 use std::{
     cell::RefCell,
-    collections::BTreeMap,
+    collections::{BTreeMap, BTreeSet, VecDeque},
     env::{current_dir, var},
     fmt::Display,
-    iter::Peekable,
     path::{Path, PathBuf},
     process::{Command, Stdio},
     rc::Rc,
-    sync::atomic::{AtomicBool, Ordering},
-    time::Duration,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::{Duration, Instant, SystemTime},
 };
 use strum::IntoEnumIterator;
+use subprocess::Popen;
 
 const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
 
-static CTRLC: AtomicBool = AtomicBool::new(false);
+const CTRLC_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// smoelius: The number of times Ctrl-C has been pressed. The first Ctrl-C lets the current removal
+// finish; the second aborts it.
+static CTRLC: AtomicUsize = AtomicUsize::new(0);
+
+// smoelius: Whether the user has been told that the first Ctrl-C was detected. The handler itself
+// prints nothing, so that the notice goes through `context.println` and respects `--quiet`.
+static CTRLC_NOTICED: AtomicBool = AtomicBool::new(false);
+
+static CTRLC_HANDLER: OnceCell<()> = OnceCell::new();
 
 #[allow(clippy::unwrap_used)]
 static NPROC_INIT: Lazy<u64> = Lazy::new(|| {
@@ -45,18 +56,67 @@ static NPROC_INIT: Lazy<u64> = Lazy::new(|| {
 // can allocate is actually 1024 plus the number of threads started by other users.
 const NPROC_ALLOWANCE: u64 = 1024;
 
-pub(crate) struct Removal {
+/// The result of running the tests with a candidate removed
+#[derive(Clone, Debug)]
+pub struct Removal {
     pub span: Span,
+    /// The removed source text
     pub text: String,
     pub outcome: Outcome,
+    // smoelius: The remaining fields are `None` for removals read from databases that predate
+    // them.
+    pub test_name: Option<String>,
+    pub kind: Option<Kind>,
+    pub timing: Option<Timing>,
+    /// The test command's exit status, or `None` if the command was not run, timed out, or exited
+    /// in a way that could not be determined
+    pub exit_status: Option<ExitStatus>,
+    pub fingerprint: Option<String>,
+    /// Whether the removal's test is expected to panic (see [`Candidate::should_panic`])
+    pub should_panic: Option<bool>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Timing {
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub wall_time: Duration,
+}
+
+/// How a test command exited
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExitStatus {
+    /// The command exited with the given code.
+    Exited(u32),
+    /// The command was terminated by the given signal.
+    Signaled(u8),
+}
+
+impl ExitStatus {
+    fn from_subprocess(status: subprocess::ExitStatus) -> Option<Self> {
+        match status {
+            subprocess::ExitStatus::Exited(code) => Some(Self::Exited(code)),
+            subprocess::ExitStatus::Signaled(signal) => Some(Self::Signaled(signal)),
+            subprocess::ExitStatus::Other(_) | subprocess::ExitStatus::Undetermined => None,
+        }
+    }
 }
 
 struct Context<'a> {
     opts: Necessist,
     root: Rc<PathBuf>,
     println: &'a dyn Fn(&dyn AsRef<str>),
+    observer: &'a dyn Observer,
+    session: &'a Session,
     framework: Box<dyn framework::Interface>,
+    framework_name: String,
     progress: Option<&'a ProgressBar>,
+    /// This run's removals, including ones carried over by `--resume`
+    removals: Vec<Removal>,
+    /// When `--time-budget` runs out
+    deadline: Option<Instant>,
+    /// The seed with which `--sample` selected candidates
+    seed: Option<u64>,
 }
 
 impl<'a> Context<'a> {
@@ -65,6 +125,8 @@ impl<'a> Context<'a> {
             opts: &self.opts,
             root: &self.root,
             println: self.println,
+            observer: self.observer,
+            session: self.session,
         }
     }
 }
@@ -73,6 +135,8 @@ pub struct LightContext<'a> {
     pub opts: &'a Necessist,
     pub root: &'a Rc<PathBuf>,
     pub println: &'a dyn Fn(&dyn AsRef<str>),
+    pub observer: &'a dyn Observer,
+    pub session: &'a Session,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -83,25 +147,68 @@ pub struct Necessist {
     pub deny: Vec<Warning>,
     pub dump: bool,
     pub dump_candidates: bool,
+    pub kind: Vec<Kind>,
     pub no_dry_run: bool,
     pub no_sqlite: bool,
     pub quiet: bool,
     pub reset: bool,
     pub resume: bool,
     pub root: Option<PathBuf>,
+    pub sample: Option<Sample>,
+    pub seed: Option<u64>,
+    pub shard: Option<Shard>,
+    pub shard_by: Option<ShardBy>,
+    pub storage: Option<Storage>,
+    pub subcommand: Option<Subcommand>,
+    pub summary: bool,
+    pub test: Vec<String>,
+    pub time_budget: Option<TimeBudget>,
     pub timeout: Option<u64>,
     pub verbose: bool,
+    pub watch: bool,
     pub test_files: Vec<PathBuf>,
     pub args: Vec<String>,
 }
 
+#[derive(Clone, Debug)]
+pub enum Subcommand {
+    /// Report how the removals of two runs recorded in the database differ
+    Compare { run_a: String, run_b: String },
+    /// Combine the most recent runs of several databases into one run in a new database
+    Merge {
+        inputs: Vec<PathBuf>,
+        output: PathBuf,
+    },
+    /// Restore files left modified by an interrupted run
+    Restore,
+    /// Report tests at least `threshold` of whose removals passed in `run` (or the most recent
+    /// run)
+    Vacuous {
+        threshold: Option<f64>,
+        run: Option<String>,
+    },
+}
+
 /// Necessist's main entrypoint.
 // smoelius: The reason `framework` is not included as a field in `Necessist` is to avoid having
 // to parameterize every function that takes a `Necessist` as an argument.
-pub fn necessist<Identifier: Applicable + Display + IntoEnumIterator + ToImplementation>(
+pub fn necessist<Identifier: Applicable + Clone + Display + IntoEnumIterator + ToImplementation>(
     opts: &Necessist,
     framework: framework::Auto<Identifier>,
 ) -> Result<()> {
+    necessist_with_observer(opts, framework, &())
+}
+
+/// Like [`necessist`], but reports events to `observer` as they occur.
+pub fn necessist_with_observer<
+    Identifier: Applicable + Clone + Display + IntoEnumIterator + ToImplementation,
+>(
+    opts: &Necessist,
+    framework: framework::Auto<Identifier>,
+    observer: &dyn Observer,
+) -> Result<()> {
+    let start = Instant::now();
+
     let opts = opts.clone();
 
     process_options(&opts)?;
@@ -115,10 +222,14 @@ pub fn necessist<Identifier: Applicable + Display + IntoEnumIterator + ToImpleme
     #[cfg(all(unix, feature = "lock_root"))]
     let _file = lock_root(&root)?;
 
+    let session = Session::default();
+
     let mut context = LightContext {
         opts: &opts,
         root: &root,
         println: &|_| {},
+        observer,
+        session: &session,
     };
 
     let println = |msg: &dyn AsRef<str>| {
@@ -129,16 +240,28 @@ pub fn necessist<Identifier: Applicable + Display + IntoEnumIterator + ToImpleme
         context.println = &println;
     }
 
-    let Some((framework, n_spans, test_file_span_map)) = prepare(&context, framework)? else {
+    let Some((framework, framework_name, n_spans, test_file_span_map, seed)) =
+        prepare(&context, framework)?
+    else {
         return Ok(());
     };
 
+    let deadline = opts
+        .time_budget
+        .and_then(|time_budget| time_budget.deadline(start));
+
     let mut context = Context {
         opts,
         root,
         println: &|_| {},
+        observer,
+        session: &session,
         framework,
+        framework_name,
         progress: None,
+        removals: Vec::new(),
+        deadline,
+        seed,
     };
 
     if !context.opts.quiet {
@@ -166,14 +289,16 @@ pub fn necessist<Identifier: Applicable + Display + IntoEnumIterator + ToImpleme
 }
 
 #[allow(clippy::type_complexity)]
-fn prepare<Identifier: Applicable + Display + IntoEnumIterator + ToImplementation>(
+fn prepare<Identifier: Applicable + Clone + Display + IntoEnumIterator + ToImplementation>(
     context: &LightContext,
     framework: framework::Auto<Identifier>,
 ) -> Result<
     Option<(
         Box<dyn framework::Interface>,
+        String,
         usize,
-        BTreeMap<SourceFile, Vec<Span>>,
+        BTreeMap<SourceFile, Vec<Candidate>>,
+        Option<u64>,
     )>,
 > {
     if context.opts.default_config {
@@ -181,6 +306,26 @@ fn prepare<Identifier: Applicable + Display + IntoEnumIterator + ToImplementatio
         return Ok(None);
     }
 
+    match &context.opts.subcommand {
+        Some(Subcommand::Compare { run_a, run_b }) => {
+            compare::compare(context, run_a, run_b)?;
+            return Ok(None);
+        }
+        Some(Subcommand::Merge { inputs, output }) => {
+            store::merge(context, inputs, output)?;
+            return Ok(None);
+        }
+        Some(Subcommand::Restore) => {
+            journal::restore(context)?;
+            return Ok(None);
+        }
+        Some(Subcommand::Vacuous { threshold, run }) => {
+            vacuous::vacuous(context, run.as_deref(), *threshold)?;
+            return Ok(None);
+        }
+        None => {}
+    }
+
     let config = config::Toml::read(context, context.root)?;
 
     if context.opts.dump {
@@ -189,19 +334,46 @@ fn prepare<Identifier: Applicable + Display + IntoEnumIterator + ToImplementatio
         return Ok(None);
     }
 
-    let mut framework = find_framework(context, framework)?;
+    journal::ensure_absent(context)?;
+
+    let (mut framework, framework_name) = find_framework(context, framework)?;
 
-    let paths = canonicalize_test_files(context)?;
+    let filter = filter::Filter::new(context)?;
 
-    let spans = framework.parse(
+    let candidates = framework.parse(
         context,
         &config,
-        &paths.iter().map(AsRef::as_ref).collect::<Vec<_>>(),
+        &filter.paths().iter().map(AsRef::as_ref).collect::<Vec<_>>(),
     )?;
 
-    let n_spans = spans.len();
+    let candidates = filter.apply(candidates);
+
+    let seed = if context.opts.sample.is_some() {
+        Some(sample_seed(context)?)
+    } else {
+        None
+    };
 
-    let test_file_span_map = build_test_file_span_map(spans);
+    let n_candidates = candidates.len();
+
+    let test_file_span_map = select(context, candidates, seed);
+
+    if let Some(seed) = seed {
+        let n_sampled = test_file_span_map.values().map(Vec::len).sum::<usize>();
+        (context.println)(&format!(
+            "Sampling {n_sampled} of {n_candidates} candidates (seed {seed})"
+        ));
+    }
+
+    context.observer.on_candidates(
+        &test_file_span_map
+            .values()
+            .flatten()
+            .cloned()
+            .collect::<Vec<_>>(),
+    );
+
+    let n_spans = test_file_span_map.values().map(Vec::len).sum::<usize>();
 
     if context.opts.dump_candidates {
         dump_candidates(context, &test_file_span_map)?;
@@ -211,95 +383,447 @@ fn prepare<Identifier: Applicable + Display + IntoEnumIterator + ToImplementatio
     (context.println)({
         let n_test_files = test_file_span_map.keys().len();
         &format!(
-            "{} candidates in {} test file{}",
+            "{} candidates in {} test file{}{}",
             n_spans,
             n_test_files,
-            if n_test_files == 1 { "" } else { "s" }
+            if n_test_files == 1 { "" } else { "s" },
+            context
+                .opts
+                .shard
+                .map(|shard| format!(" (shard {shard})"))
+                .unwrap_or_default()
         )
     });
 
-    Ok(Some((framework, n_spans, test_file_span_map)))
+    Ok(Some((
+        framework,
+        framework_name,
+        n_spans,
+        test_file_span_map,
+        seed,
+    )))
 }
 
-fn run(mut context: Context, test_file_span_map: BTreeMap<SourceFile, Vec<Span>>) -> Result<()> {
-    ctrlc::set_handler(|| CTRLC.store(true, Ordering::SeqCst))?;
+/// Keeps the candidates selected by `--shard` and, if `seed` is given, `--sample`
+#[allow(clippy::mutable_key_type)]
+fn select(
+    context: &LightContext,
+    candidates: Vec<Candidate>,
+    seed: Option<u64>,
+) -> BTreeMap<SourceFile, Vec<Candidate>> {
+    let mut test_file_span_map = build_test_file_span_map(candidates);
+
+    if let Some(shard) = context.opts.shard {
+        test_file_span_map = shard::shard(
+            test_file_span_map,
+            shard,
+            context.opts.shard_by.unwrap_or_default(),
+        );
+    }
 
-    let past_removals = past_removals_init_lazy(&context.light())?;
+    if let Some((sample, seed)) = context.opts.sample.zip(seed) {
+        test_file_span_map = sample::sample(test_file_span_map, sample, seed);
+    }
+
+    test_file_span_map
+}
 
-    let mut past_removal_iter = past_removals.into_iter().peekable();
+/// Returns the seed with which to `--sample`: the seed recorded for the run when there is a
+/// database, and otherwise `--seed` or a random seed.
+fn sample_seed(context: &LightContext) -> Result<u64> {
+    let recorded = if context.opts.no_sqlite || context.opts.dump_candidates {
+        None
+    } else {
+        store_init_lazy(context)?
+            .borrow()
+            .as_ref()
+            .and_then(store::Store::seed)
+    };
 
-    for (test_file, spans) in test_file_span_map {
-        let mut span_iter = spans.iter().peekable();
+    Ok(recorded
+        .or(context.opts.seed)
+        .unwrap_or_else(sample::random_seed))
+}
 
-        let (mismatch, n) = skip_past_removals(&mut span_iter, &mut past_removal_iter);
+fn run(
+    mut context: Context,
+    test_file_span_map: BTreeMap<SourceFile, Vec<Candidate>>,
+) -> Result<()> {
+    // smoelius: A process can have only one Ctrl-C handler, so it is installed by the first session
+    // only. Each session starts with the count cleared.
+    CTRLC_HANDLER.get_or_try_init(|| ctrlc::set_handler(ctrlc_handler))?;
+    CTRLC.store(0, Ordering::SeqCst);
+    CTRLC_NOTICED.store(false, Ordering::SeqCst);
+
+    let test_files = test_file_span_map
+        .keys()
+        .map(|test_file| test_file.to_path_buf())
+        .collect::<BTreeSet<_>>();
+
+    let test_file_span_map = relocate_past_removals(&mut context, test_file_span_map)?;
+
+    for (test_file, candidates) in test_file_span_map {
+        if let Some(reason) = run_test_file(&mut context, &test_file, &candidates)? {
+            return stop(&context, reason);
+        }
+    }
 
-        update_progress(&context, mismatch, n)?;
+    context.progress.map(ProgressBar::finish);
 
-        if span_iter.peek().is_none() {
-            continue;
+    report(&context);
+
+    if context.opts.watch {
+        return watch(context, test_files);
+    }
+
+    Ok(())
+}
+
+/// Dry runs `test_file` and then tries each of `candidates`. Returns why the run should stop, if it
+/// should.
+fn run_test_file(
+    context: &mut Context,
+    test_file: &SourceFile,
+    candidates: &[Candidate],
+) -> Result<Option<Stop>> {
+    if !context.opts.no_dry_run {
+        (context.println)(&format!(
+            "{}: dry running",
+            util::strip_current_dir(test_file).to_string_lossy()
+        ));
+
+        let result = context.framework.dry_run(&context.light(), test_file);
+
+        context.observer.on_dry_run(test_file, &result);
+
+        if let Some(reason) = should_stop(context) {
+            return Ok(Some(reason));
         }
 
-        if !context.opts.no_dry_run {
-            (context.println)(&format!(
-                "{}: dry running",
-                util::strip_current_dir(&test_file).to_string_lossy()
-            ));
+        if let Err(error) = &result {
+            source_warn(
+                &context.light(),
+                Warning::DryRunFailed,
+                test_file,
+                &format!("dry run failed: {error}"),
+                WarnFlags::empty(),
+            )?;
+        }
 
-            let result = context.framework.dry_run(&context.light(), &test_file);
+        if result.is_err() {
+            update_progress(context, false, candidates.len())?;
+            return Ok(None);
+        }
+    }
 
-            if let Err(error) = &result {
-                source_warn(
-                    &context.light(),
-                    Warning::DryRunFailed,
-                    &test_file,
-                    &format!("dry run failed: {error}"),
-                    WarnFlags::empty(),
-                )?;
-            }
+    (context.println)(&format!(
+        "{}: mutilating",
+        util::strip_current_dir(test_file).to_string_lossy()
+    ));
 
-            if CTRLC.load(Ordering::SeqCst) {
-                bail!("Ctrl-C detected");
-            }
+    for candidate in candidates {
+        context.observer.on_removal_started(candidate);
+
+        // smoelius: The terminal delivers Ctrl-C to the test processes as well as to Necessist. So
+        // if the first Ctrl-C arrives during a removal, that removal's outcome cannot be trusted,
+        // and the removal is attempted again.
+        let (text, outcome, timing) = loop {
+            let n_ctrlc = ctrlc_count();
+
+            let start = SystemTime::now();
+            let instant = Instant::now();
+
+            let (text, outcome) = attempt_removal(context, &candidate.span)?;
 
-            if result.is_err() {
-                update_progress(&context, false, span_iter.count())?;
-                continue;
+            let timing = Timing {
+                start,
+                end: SystemTime::now(),
+                wall_time: instant.elapsed(),
+            };
+
+            if ctrlc_count() == n_ctrlc {
+                break (text, outcome, timing);
             }
+
+            notice_ctrlc(&context.light());
+        };
+
+        if let Some((outcome, exit_status)) = outcome {
+            emit(context, candidate, &text, outcome, timing, exit_status)?;
+        } else {
+            context.observer.on_removal_finished(candidate, None);
         }
 
-        (context.println)(&format!(
-            "{}: mutilating",
-            util::strip_current_dir(&test_file).to_string_lossy()
-        ));
+        update_progress(context, false, 1)?;
 
-        loop {
-            let (mismatch, n) = skip_past_removals(&mut span_iter, &mut past_removal_iter);
+        if let Some(reason) = should_stop(context) {
+            return Ok(Some(reason));
+        }
+    }
 
-            update_progress(&context, mismatch, n)?;
+    Ok(None)
+}
 
-            let Some(span) = span_iter.next() else {
-                break;
-            };
+/// Waits for `test_files` to change, and re-checks the candidates of each one that does. Returns
+/// when Ctrl-C is pressed.
+fn watch(mut context: Context, test_files: BTreeSet<PathBuf>) -> Result<()> {
+    context.progress = None;
 
-            let (text, outcome) = attempt_removal(&context, span)?;
+    let watcher = watch::Watcher::new(test_files)?;
 
-            if CTRLC.load(Ordering::SeqCst) {
-                bail!("Ctrl-C detected");
-            }
+    let mut announce = true;
+
+    loop {
+        if announce {
+            (context.println)(&"Watching for changes (press Ctrl-C to stop)");
+        }
 
-            if let Some(outcome) = outcome {
-                emit(&mut context, span, &text, outcome)?;
+        let Some(changed) = watcher.wait(|| ctrlc_count() > 0)? else {
+            return Ok(());
+        };
+
+        // smoelius: Necessist's own writes to a file (i.e., removing a candidate and then restoring
+        // the file) produce events too. Those leave the file as it was last parsed, and are
+        // ignored.
+        let edited = changed
+            .into_iter()
+            .filter(|test_file| edited_since_parsed(&context, test_file))
+            .collect::<Vec<_>>();
+
+        announce = !edited.is_empty();
+
+        // smoelius: `--time-budget` limits each re-check, not the time spent watching.
+        context.deadline = context
+            .opts
+            .time_budget
+            .and_then(|time_budget| time_budget.deadline(Instant::now()));
+
+        for test_file in edited {
+            match recheck_test_file(&mut context, &test_file)? {
+                Some(Stop::CtrlC) => return stop(&context, Stop::CtrlC),
+                Some(Stop::TimeBudget) => {
+                    note(
+                        &context.light(),
+                        "Stopped re-checking because the time budget was exhausted",
+                    );
+                    break;
+                }
+                None => {}
             }
+        }
+    }
+}
+
+fn edited_since_parsed(context: &Context, test_file: &Path) -> bool {
+    let Ok(contents) = std::fs::read_to_string(test_file) else {
+        return false;
+    };
+
+    context
+        .session
+        .source_files
+        .borrow()
+        .get(test_file)
+        .map_or(true, |source_file| source_file.contents() != contents)
+}
+
+/// Re-parses `test_file`, keeps the removals recorded for its unchanged candidates, and tries its
+/// other candidates again. Returns why watching should stop, if it should.
+fn recheck_test_file(context: &mut Context, test_file: &Path) -> Result<Option<Stop>> {
+    context.session.source_files.borrow_mut().remove(test_file);
+
+    let light = LightContext {
+        opts: &context.opts,
+        root: &context.root,
+        println: context.println,
+        observer: context.observer,
+        session: context.session,
+    };
+
+    let config = config::Toml::read(&light, light.root)?;
+
+    let filter = filter::Filter::new(&light)?;
 
-            update_progress(&context, false, 1)?;
+    // smoelius: `--shard` and `--sample` select among all of the candidates, so when either is
+    // given, every test file is re-parsed.
+    let paths = if context.opts.shard.is_some() || context.opts.sample.is_some() {
+        filter.paths().iter().map(AsRef::as_ref).collect()
+    } else {
+        vec![test_file]
+    };
+
+    let mut candidates = match context.framework.parse(&light, &config, &paths) {
+        Ok(candidates) => select(&light, filter.apply(candidates), context.seed)
+            .into_iter()
+            .find_map(|(source_file, candidates)| {
+                if source_file.as_ref() == test_file {
+                    Some(candidates)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default(),
+        Err(error) => {
+            (context.println)(&format!(
+                "{}: failed to parse; waiting for further changes: {error:#}",
+                util::strip_current_dir(test_file).to_string_lossy()
+            ));
+            return Ok(None);
+        }
+    };
+
+    let (stale, removals) = std::mem::take(&mut context.removals)
+        .into_iter()
+        .partition::<Vec<_>, _>(|removal| removal.span.source_file.as_ref() == test_file);
+    context.removals = removals;
+
+    let source_file = SourceFile::new(&context.light(), test_file.to_path_buf())?;
+
+    let lines = source_file.contents().lines().collect::<Vec<_>>();
+
+    // smoelius: A candidate whose fingerprint matches that of a stale removal is unchanged, and its
+    // result is kept. Only the remaining candidates are tried again.
+    let mut stale_by_fingerprint = BTreeMap::<_, VecDeque<_>>::new();
+    for removal in &stale {
+        if let Some(fingerprint) = &removal.fingerprint {
+            stale_by_fingerprint
+                .entry(fingerprint.clone())
+                .or_default()
+                .push_back(removal);
         }
     }
 
-    context.progress.map(ProgressBar::finish);
+    let mut kept = Vec::new();
+    let mut changed = Vec::new();
+
+    for candidate in candidates.drain(..) {
+        let fingerprint = candidate.fingerprint(&lines)?;
+        if let Some(removal) = stale_by_fingerprint
+            .get_mut(&fingerprint)
+            .and_then(VecDeque::pop_front)
+        {
+            kept.push(relocate(&candidate, fingerprint, removal)?);
+        } else {
+            let previously_passed = stale.iter().any(|removal| {
+                removal.outcome == Outcome::Passed
+                    && ((removal.span.start == candidate.span.start
+                        && removal.span.end == candidate.span.end)
+                        || removal.fingerprint.as_ref() == Some(&fingerprint))
+            });
+            changed.push((candidate, previously_passed));
+        }
+    }
+
+    // smoelius: Changed candidates whose removals previously passed are tried first. The sort is
+    // stable, so the candidates are otherwise tried in source order.
+    changed.sort_by_key(|&(_, previously_passed)| !previously_passed);
+    let changed = changed
+        .into_iter()
+        .map(|(candidate, _)| candidate)
+        .collect::<Vec<_>>();
+
+    let store = store_init_lazy(&context.light())?;
+
+    if let Some(store) = store.borrow_mut().as_mut() {
+        for removal in &stale {
+            store.delete(&removal.span)?;
+        }
+
+        for removal in &kept {
+            store.insert(&context.framework_name, removal)?;
+        }
+    }
+
+    (context.println)(&format!(
+        "{}: changed; re-checking {} candidate{}{}",
+        util::strip_current_dir(test_file).to_string_lossy(),
+        changed.len(),
+        if changed.len() == 1 { "" } else { "s" },
+        if kept.is_empty() {
+            String::new()
+        } else {
+            format!(" ({} unchanged)", kept.len())
+        }
+    ));
+
+    context.removals.extend(kept);
+
+    if changed.is_empty() {
+        return Ok(None);
+    }
+
+    run_test_file(context, &source_file, &changed)
+}
+
+fn ctrlc_handler() {
+    CTRLC.fetch_add(1, Ordering::SeqCst);
+}
+
+fn ctrlc_count() -> usize {
+    CTRLC.load(Ordering::SeqCst)
+}
+
+/// Tells the user that the current removal will be finished, the first time Ctrl-C is detected
+fn notice_ctrlc(context: &LightContext) {
+    if ctrlc_count() > 0 && !CTRLC_NOTICED.swap(true, Ordering::SeqCst) {
+        note(
+            context,
+            "Ctrl-C detected; finishing the current removal (press Ctrl-C again to abort)",
+        );
+    }
+}
+
+/// Why a run stopped before trying every candidate
+#[derive(Clone, Copy, Debug)]
+enum Stop {
+    CtrlC,
+    TimeBudget,
+}
+
+fn should_stop(context: &Context) -> Option<Stop> {
+    if ctrlc_count() > 0 {
+        Some(Stop::CtrlC)
+    } else if time_budget::is_exhausted(context.deadline, Instant::now()) {
+        Some(Stop::TimeBudget)
+    } else {
+        None
+    }
+}
+
+fn stop(context: &Context, reason: Stop) -> Result<()> {
+    context.progress.map(ProgressBar::abandon);
+
+    report(context);
+
+    let msg = match reason {
+        Stop::CtrlC => "Stopped after Ctrl-C",
+        Stop::TimeBudget => "Stopped because the time budget was exhausted",
+    };
+
+    note(
+        &context.light(),
+        &if context.opts.no_sqlite {
+            msg.to_owned()
+        } else {
+            format!("{msg}; pass --resume to continue where this run left off")
+        },
+    );
 
     Ok(())
 }
 
+/// Prints the summary requested by `--summary`, and, if only some candidates may have been tried,
+/// an estimate of the fraction of all candidates whose removals would pass
+fn report(context: &Context) {
+    if context.opts.summary {
+        summary::summarize(&context.light(), &context.removals);
+    }
+
+    if context.opts.sample.is_some() || context.opts.time_budget.is_some() {
+        sample::estimate(&context.light(), &context.removals);
+    }
+}
+
 macro_rules! incompatible {
     ($opts:ident, $x:ident, $y:ident) => {
         ensure!(
@@ -317,6 +841,8 @@ fn process_options(opts: &Necessist) -> Result<()> {
     incompatible!(opts, dump, reset);
     incompatible!(opts, dump, resume);
     incompatible!(opts, dump, no_sqlite);
+    incompatible!(opts, dump, watch);
+    incompatible!(opts, quiet, summary);
     incompatible!(opts, quiet, verbose);
     incompatible!(opts, reset, no_sqlite);
     incompatible!(opts, resume, no_sqlite);
@@ -358,75 +884,148 @@ fn dump(context: &LightContext, removals: &[Removal]) {
         other_than_passed |= removal.outcome != Outcome::Passed;
     }
 
+    if context.opts.summary {
+        summary::summarize(context, removals);
+    }
+
     if !context.opts.verbose && other_than_passed {
         note(context, "More output would be produced with --verbose");
     }
 }
 
-fn find_framework<Identifier: Applicable + Display + IntoEnumIterator + ToImplementation>(
+fn find_framework<
+    Identifier: Applicable + Clone + Display + IntoEnumIterator + ToImplementation,
+>(
     context: &LightContext,
     identifier: framework::Auto<Identifier>,
-) -> Result<Box<dyn framework::Interface>> {
-    let implementation = identifier.to_implementation(context)?;
+) -> Result<(Box<dyn framework::Interface>, String)> {
+    let identifier = identifier
+        .resolve(context)?
+        .ok_or_else(|| anyhow!("Found no applicable frameworks"))?;
 
-    drop(identifier);
+    let implementation = identifier.to_implementation(context)?;
 
-    implementation.ok_or_else(|| anyhow!("Found no applicable frameworks"))
+    implementation
+        .map(|implementation| (implementation, identifier.to_string()))
+        .ok_or_else(|| anyhow!("Found no applicable frameworks"))
 }
 
-fn canonicalize_test_files(context: &LightContext) -> Result<Vec<PathBuf>> {
-    context
-        .opts
-        .test_files
-        .iter()
-        .map(|path| {
-            let path_buf = path
-                .canonicalize()
-                .with_context(|| format!("Failed to canonicalize {path:?}"))?;
-            ensure!(
-                path_buf.starts_with(context.root.as_path()),
-                "{:?} is not in {:?}",
-                path_buf,
-                context.root
-            );
-            Ok(path_buf)
-        })
-        .collect::<Result<Vec<_>>>()
-}
-
-#[must_use]
-fn skip_past_removals<'a, I, J>(
-    span_iter: &mut Peekable<I>,
-    removal_iter: &mut Peekable<J>,
-) -> (bool, usize)
-where
-    I: Iterator<Item = &'a Span>,
-    J: Iterator<Item = Removal>,
-{
-    let mut mismatch = false;
+/// Matches past removals to candidates, first by span and then by fingerprint. A past removal
+/// matched by fingerprint alone is moved to its candidate's new span. Returns the candidates that
+/// remain to be tried.
+#[allow(clippy::mutable_key_type)]
+fn relocate_past_removals(
+    context: &mut Context,
+    test_file_span_map: BTreeMap<SourceFile, Vec<Candidate>>,
+) -> Result<BTreeMap<SourceFile, Vec<Candidate>>> {
+    let past_removals = past_removals_init_lazy(&context.light())?;
+
+    let mut removals_by_span = past_removals
+        .into_iter()
+        .map(|removal| (removal.span.to_string(), removal))
+        .collect::<BTreeMap<_, _>>();
+
     let mut n = 0;
-    while let Some(&span) = span_iter.peek() {
-        let Some(removal) = removal_iter.peek() else {
-            break;
-        };
-        match span.cmp(&removal.span) {
-            std::cmp::Ordering::Less => {
-                mismatch = true;
-                break;
-            }
-            std::cmp::Ordering::Equal => {
-                let _ = span_iter.next();
-                let _removal = removal_iter.next();
+    let mut unmatched = Vec::new();
+
+    // smoelius: Removals read from databases that predate fingerprints are matched by span alone.
+    for (test_file, candidates) in test_file_span_map {
+        let lines = test_file.contents().lines().collect::<Vec<_>>();
+        for candidate in candidates {
+            let fingerprint = candidate.fingerprint(&lines)?;
+            let key = candidate.span.to_string();
+            if removals_by_span.get(&key).map_or(false, |removal| {
+                removal
+                    .fingerprint
+                    .as_ref()
+                    .map_or(true, |other| *other == fingerprint)
+            }) {
+                context.removals.extend(removals_by_span.remove(&key));
                 n += 1;
+            } else {
+                unmatched.push((candidate, fingerprint));
             }
-            std::cmp::Ordering::Greater => {
-                mismatch = true;
-                let _removal = removal_iter.next();
-            }
         }
     }
 
-    (mismatch, n)
+    let mut keys_by_fingerprint = BTreeMap::<_, VecDeque<_>>::new();
+    for (key, removal) in &removals_by_span {
+        if let Some(fingerprint) = &removal.fingerprint {
+            keys_by_fingerprint
+                .entry(fingerprint.clone())
+                .or_default()
+                .push_back(key.clone());
+        }
+    }
+
+    let mut relocated = Vec::new();
+    let mut remaining = Vec::new();
+
+    for (candidate, fingerprint) in unmatched {
+        let removal = keys_by_fingerprint
+            .get_mut(&fingerprint)
+            .and_then(VecDeque::pop_front)
+            .and_then(|key| removals_by_span.remove(&key));
+        if let Some(removal) = removal {
+            relocated.push((candidate, fingerprint, removal));
+        } else {
+            remaining.push(candidate);
+        }
+    }
+
+    // smoelius: A past removal whose span is occupied by a candidate with a different fingerprint
+    // is stale. It is deleted so that the candidate's new outcome, or the relocated removal, can be
+    // recorded.
+    let superseded = relocated
+        .iter()
+        .map(|(candidate, _, _)| candidate)
+        .chain(&remaining)
+        .filter_map(|candidate| removals_by_span.remove(&candidate.span.to_string()))
+        .collect::<Vec<_>>();
+
+    let mismatch = !superseded.is_empty() || !removals_by_span.is_empty();
+
+    let store = store_init_lazy(&context.light())?;
+
+    let mut relocated_removals = Vec::new();
+
+    if let Some(store) = store.borrow_mut().as_mut() {
+        for removal in relocated
+            .iter()
+            .map(|(_, _, removal)| removal)
+            .chain(&superseded)
+        {
+            store.delete(&removal.span)?;
+        }
+
+        for (candidate, fingerprint, removal) in &relocated {
+            let removal = relocate(candidate, fingerprint.clone(), removal)?;
+            store.insert(&context.framework_name, &removal)?;
+            relocated_removals.push(removal);
+        }
+    }
+
+    context.removals.extend(relocated_removals);
+
+    update_progress(context, mismatch, n + relocated.len())?;
+
+    Ok(build_test_file_span_map(remaining))
+}
+
+/// Returns `removal`'s result recorded for `candidate`, which has the same fingerprint but possibly
+/// a different span
+fn relocate(candidate: &Candidate, fingerprint: String, removal: &Removal) -> Result<Removal> {
+    Ok(Removal {
+        span: candidate.span.clone(),
+        text: candidate.span.source_text()?,
+        outcome: removal.outcome,
+        test_name: Some(candidate.test_name.clone()),
+        kind: Some(candidate.kind),
+        timing: removal.timing,
+        exit_status: removal.exit_status,
+        fingerprint: Some(fingerprint),
+        should_panic: Some(candidate.should_panic),
+    })
 }
 
 fn update_progress(context: &Context, mismatch: bool, n: usize) -> Result<()> {
@@ -446,16 +1045,18 @@ fn update_progress(context: &Context, mismatch: bool, n: usize) -> Result<()> {
     Ok(())
 }
 
-fn build_test_file_span_map(mut spans: Vec<Span>) -> BTreeMap<SourceFile, Vec<Span>> {
+fn build_test_file_span_map(
+    mut candidates: Vec<Candidate>,
+) -> BTreeMap<SourceFile, Vec<Candidate>> {
     let mut test_file_span_map = BTreeMap::new();
 
-    spans.sort();
+    candidates.sort();
 
-    for span in spans {
+    for candidate in candidates {
         let test_file_spans = test_file_span_map
-            .entry(span.source_file.clone())
+            .entry(candidate.span.source_file.clone())
             .or_insert_with(Vec::default);
-        test_file_spans.push(span);
+        test_file_spans.push(candidate);
     }
 
     test_file_span_map
@@ -463,9 +1064,9 @@ fn build_test_file_span_map(mut spans: Vec<Span>) -> BTreeMap<SourceFile, Vec<Sp
 
 fn dump_candidates(
     context: &LightContext,
-    test_file_span_map: &BTreeMap<SourceFile, Vec<Span>>,
+    test_file_span_map: &BTreeMap<SourceFile, Vec<Candidate>>,
 ) -> Result<()> {
-    for span in test_file_span_map.values().flatten() {
+    for Candidate { span, .. } in test_file_span_map.values().flatten() {
         let text = span.source_text()?;
 
         (context.println)(&format!("{}: `{}`", span.to_console_string(), text));
@@ -474,13 +1075,21 @@ fn dump_candidates(
     Ok(())
 }
 
-fn attempt_removal(context: &Context, span: &Span) -> Result<(String, Option<Outcome>)> {
-    let (text, _backup) = span.remove()?;
+#[allow(clippy::type_complexity)]
+fn attempt_removal(
+    context: &Context,
+    span: &Span,
+) -> Result<(String, Option<(Outcome, Option<ExitStatus>)>)> {
+    let (text, _journaled) = span.remove(&context.light())?;
 
     let exec = context.framework.exec(&context.light(), span)?;
 
+    if ctrlc_count() > 1 {
+        bail!("Ctrl-C detected twice; aborting");
+    }
+
     let Some((exec, postprocess)) = exec else {
-        return Ok((text, Some(Outcome::Nonbuildable)));
+        return Ok((text, Some((Outcome::Nonbuildable, None))));
     };
 
     debug!("{:?}", exec);
@@ -488,14 +1097,12 @@ fn attempt_removal(context: &Context, span: &Span) -> Result<(String, Option<Out
     let nprocs_prev = set_soft_rlimit(Resource::NPROC, *NPROC_INIT + NPROC_ALLOWANCE)?;
 
     let mut popen = exec.popen()?;
-    let status = if let Some(dur) = timeout(&context.opts) {
-        popen.wait_timeout(dur)?
-    } else {
-        popen.wait().map(Option::Some)?
-    };
+    let status = wait_unless_ctrlc_twice(&context.light(), &mut popen, timeout(&context.opts));
 
     set_soft_rlimit(Resource::NPROC, nprocs_prev)?;
 
+    let status = status?;
+
     if status.is_some() {
         if let Some(postprocess) = postprocess {
             if !postprocess(&context.light(), popen)? {
@@ -509,35 +1116,66 @@ fn attempt_removal(context: &Context, span: &Span) -> Result<(String, Option<Out
     }
 
     let Some(status) = status else {
-        return Ok((text, Some(Outcome::TimedOut)));
+        return Ok((text, Some((Outcome::TimedOut, None))));
     };
 
     Ok((
         text,
-        Some(if status.success() {
-            Outcome::Passed
-        } else {
-            Outcome::Failed
-        }),
+        Some((
+            if status.success() {
+                Outcome::Passed
+            } else {
+                Outcome::Failed
+            },
+            ExitStatus::from_subprocess(status),
+        )),
     ))
 }
 
 #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
-fn emit(context: &mut Context, span: &Span, text: &str, outcome: Outcome) -> Result<()> {
+fn emit(
+    context: &mut Context,
+    candidate: &Candidate,
+    text: &str,
+    outcome: Outcome,
+    timing: Timing,
+    exit_status: Option<ExitStatus>,
+) -> Result<()> {
+    let Candidate {
+        span,
+        test_name,
+        kind,
+        should_panic,
+    } = candidate;
+
     let removal = Removal {
         span: span.clone(),
         text: text.to_owned(),
         outcome,
+        test_name: Some(test_name.clone()),
+        kind: Some(*kind),
+        timing: Some(timing),
+        exit_status,
+        fingerprint: Some(
+            candidate.fingerprint(&span.source_file.contents().lines().collect::<Vec<_>>())?,
+        ),
+        should_panic: Some(*should_panic),
     };
 
-    let sqlite = sqlite_init_lazy(&context.light())?;
+    let store = store_init_lazy(&context.light())?;
 
-    if let Some(sqlite) = sqlite.borrow_mut().as_mut() {
-        sqlite::insert(sqlite, &removal)?;
+    if let Some(store) = store.borrow_mut().as_mut() {
+        store.insert(&context.framework_name, &removal)?;
     }
 
+    context
+        .observer
+        .on_removal_finished(candidate, Some(&removal));
+
     emit_to_console(&context.light(), &removal);
 
+    context.removals.push(removal);
+
     Ok(())
 }
 
@@ -546,11 +1184,13 @@ fn emit_to_console(context: &LightContext, removal: &Removal) {
         span,
         text,
         outcome,
+        should_panic,
+        ..
     } = removal;
 
     if !context.opts.quiet && (context.opts.verbose || *outcome == Outcome::Passed) {
         let msg = format!(
-            "{}: `{}` {}",
+            "{}: `{}` {}{}",
             span.to_console_string(),
             text,
             if std::io::stdout().is_terminal() {
@@ -558,59 +1198,50 @@ fn emit_to_console(context: &LightContext, removal: &Removal) {
             } else {
                 Style::default()
             }
-            .paint(outcome.to_string())
+            .paint(outcome.to_string()),
+            if *should_panic == Some(true) {
+                " (test should panic)"
+            } else {
+                ""
+            }
         );
         (context.println)(&msg);
     }
 }
 
-fn sqlite_init_lazy(context: &LightContext) -> Result<Rc<RefCell<Option<sqlite::Sqlite>>>> {
-    let (sqlite, _) = sqlite_and_past_removals_init_lazy(context)?;
-    Ok(sqlite)
+fn store_init_lazy<'a>(context: &LightContext<'a>) -> Result<&'a RefCell<Option<store::Store>>> {
+    let (store, _) = store_and_past_removals_init_lazy(context)?;
+    Ok(store)
 }
 
 fn past_removals_init_lazy(context: &LightContext) -> Result<Vec<Removal>> {
-    let (_, past_removals) = sqlite_and_past_removals_init_lazy(context)?;
+    let (_, past_removals) = store_and_past_removals_init_lazy(context)?;
     Ok(past_removals.take())
 }
 
-thread_local! {
-    #[allow(clippy::type_complexity)]
-    static SQLITE_AND_PAST_REMOVALS: OnceCell<(Rc<RefCell<Option<sqlite::Sqlite>>>, Rc<RefCell<Vec<Removal>>>)> = OnceCell::new();
-}
-
 #[allow(clippy::type_complexity)]
-fn sqlite_and_past_removals_init_lazy(
-    context: &LightContext,
-) -> Result<(
-    Rc<RefCell<Option<sqlite::Sqlite>>>,
-    Rc<RefCell<Vec<Removal>>>,
-)> {
-    SQLITE_AND_PAST_REMOVALS.with(|sqlite_and_past_removals| {
-        sqlite_and_past_removals
-            .get_or_try_init(|| {
-                if context.opts.no_sqlite {
-                    Ok((
-                        Rc::new(RefCell::new(None)),
-                        Rc::new(RefCell::new(Vec::new())),
-                    ))
-                } else {
-                    let (sqlite, mut past_removals) = sqlite::init(
-                        context,
-                        context.root,
-                        context.opts.dump,
-                        context.opts.reset,
-                        context.opts.resume,
-                    )?;
-                    past_removals.sort_by(|left, right| left.span.cmp(&right.span));
-                    Ok((
-                        Rc::new(RefCell::new(Some(sqlite))),
-                        Rc::new(RefCell::new(past_removals)),
-                    ))
-                }
-            })
-            .map(Clone::clone)
-    })
+fn store_and_past_removals_init_lazy<'a>(
+    context: &LightContext<'a>,
+) -> Result<(&'a RefCell<Option<store::Store>>, &'a RefCell<Vec<Removal>>)> {
+    let (store, past_removals) = context
+        .session
+        .store_and_past_removals
+        .get_or_try_init(|| {
+            if context.opts.no_sqlite {
+                Ok::<_, anyhow::Error>((RefCell::new(None), RefCell::new(Vec::new())))
+            } else {
+                let (store, mut past_removals) = store::init(
+                    context,
+                    context.root,
+                    context.opts.dump,
+                    context.opts.reset,
+                    context.opts.resume,
+                )?;
+                past_removals.sort_by(|left, right| left.span.cmp(&right.span));
+                Ok((RefCell::new(Some(store)), RefCell::new(past_removals)))
+            }
+        })?;
+    Ok((store, past_removals))
 }
 
 fn set_soft_rlimit(resource: Resource, limit: u64) -> Result<u64> {
@@ -619,6 +1250,42 @@ fn set_soft_rlimit(resource: Resource, limit: u64) -> Result<u64> {
     Ok(soft)
 }
 
+/// Waits for `popen` to exit, or for `timeout` to elapse. If Ctrl-C is pressed a second time while
+/// waiting, the process is killed and an error is returned. A notice is printed the first time
+/// Ctrl-C is detected.
+fn wait_unless_ctrlc_twice(
+    context: &LightContext,
+    popen: &mut Popen,
+    timeout: Option<Duration>,
+) -> Result<Option<subprocess::ExitStatus>> {
+    let instant = Instant::now();
+
+    loop {
+        let remaining = timeout.map(|timeout| timeout.saturating_sub(instant.elapsed()));
+
+        if remaining == Some(Duration::ZERO) {
+            return Ok(None);
+        }
+
+        let interval = remaining.map_or(CTRLC_POLL_INTERVAL, |remaining| {
+            std::cmp::min(remaining, CTRLC_POLL_INTERVAL)
+        });
+
+        if let Some(status) = popen.wait_timeout(interval)? {
+            return Ok(Some(status));
+        }
+
+        notice_ctrlc(context);
+
+        if ctrlc_count() > 1 {
+            let pid = popen.pid().ok_or_else(|| anyhow!("Failed to get pid"))?;
+            transitive_kill(pid)?;
+            let _ = popen.wait()?;
+            bail!("Ctrl-C detected twice; aborting");
+        }
+    }
+}
+
 fn timeout(opts: &Necessist) -> Option<Duration> {
     match opts.timeout {
         None => Some(DEFAULT_TIMEOUT),
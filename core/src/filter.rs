@@ -0,0 +1,161 @@
+use crate::{Candidate, Kind, LightContext};
+use anyhow::{bail, ensure, Context, Result};
+use regex::Regex;
+use std::{
+    collections::BTreeSet,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+/// The candidates selected by `TEST_FILES`, `--test`, and `--kind`
+pub(crate) struct Filter {
+    /// The test files to parse, canonicalized
+    paths: Vec<PathBuf>,
+    /// For each of `TEST_FILES`, its canonicalized path and the lines it selects (`None` means all
+    /// of them)
+    selectors: Vec<(PathBuf, Option<RangeInclusive<usize>>)>,
+    tests: Vec<Regex>,
+    kinds: Vec<Kind>,
+}
+
+impl Filter {
+    pub fn new(context: &LightContext) -> Result<Self> {
+        let selectors = context
+            .opts
+            .test_files
+            .iter()
+            .map(|test_file| {
+                let (path, lines) = parse_selector(test_file)?;
+                let path_buf = path
+                    .canonicalize()
+                    .with_context(|| format!("Failed to canonicalize {path:?}"))?;
+                ensure!(
+                    path_buf.starts_with(context.root.as_path()),
+                    "{:?} is not in {:?}",
+                    path_buf,
+                    context.root
+                );
+                Ok((path_buf, lines))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let paths = selectors
+            .iter()
+            .map(|(path_buf, _)| path_buf.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let tests = context
+            .opts
+            .test
+            .iter()
+            .map(|pattern| compile_test_pattern(pattern))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            paths,
+            selectors,
+            tests,
+            kinds: context.opts.kind.clone(),
+        })
+    }
+
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub fn apply(&self, candidates: Vec<Candidate>) -> Vec<Candidate> {
+        candidates
+            .into_iter()
+            .filter(|candidate| self.selects(candidate))
+            .collect()
+    }
+
+    fn selects(&self, candidate: &Candidate) -> bool {
+        let span = &candidate.span;
+
+        let selected_by_path = self.selectors.is_empty()
+            || self.selectors.iter().any(|(path_buf, lines)| {
+                span.source_file.as_ref() == path_buf.as_path()
+                    && lines.as_ref().map_or(true, |lines| {
+                        span.start.line <= *lines.end() && *lines.start() <= span.end.line
+                    })
+            });
+
+        let selected_by_test = self.tests.is_empty()
+            || self
+                .tests
+                .iter()
+                .any(|re| re.is_match(&candidate.test_name));
+
+        let selected_by_kind = self.kinds.is_empty() || self.kinds.contains(&candidate.kind);
+
+        selected_by_path && selected_by_test && selected_by_kind
+    }
+}
+
+/// Splits a `TEST_FILES` argument of the form `path:line` or `path:line-line` into its path and
+/// lines. An argument that names an existing file is taken to be a path, even if it contains a
+/// colon.
+fn parse_selector(test_file: &Path) -> Result<(PathBuf, Option<RangeInclusive<usize>>)> {
+    let s = test_file.to_string_lossy();
+
+    let Some((path, lines)) = s.rsplit_once(':') else {
+        return Ok((test_file.to_path_buf(), None));
+    };
+
+    if test_file.try_exists()? || !lines.chars().all(|ch| ch.is_ascii_digit() || ch == '-') {
+        return Ok((test_file.to_path_buf(), None));
+    }
+
+    let (start, end) = lines.split_once('-').unwrap_or((lines, lines));
+    let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) else {
+        bail!("`{}` is not of the form `path:line` or `path:line-line`", s);
+    };
+    ensure!(
+        1 <= start && start <= end,
+        "Lines in `{}` must satisfy 1 <= start <= end",
+        s
+    );
+
+    Ok((PathBuf::from(path), Some(start..=end)))
+}
+
+// smoelius: Test names can contain spaces and punctuation (e.g., Mocha's), so unlike the patterns
+// in necessist.toml, only `*` is special.
+fn compile_test_pattern(pattern: &str) -> Result<Regex> {
+    let escaped = pattern
+        .split('*')
+        .map(regex::escape)
+        .collect::<Vec<_>>()
+        .join(".*");
+
+    Regex::new(&format!("^{escaped}$")).map_err(Into::into)
+}
+
+#[allow(clippy::type_complexity)]
+#[test]
+fn selectors() {
+    const EXAMPLES: &[(&str, &str, Option<(usize, usize)>)] = &[
+        ("src/lib.rs", "src/lib.rs", None),
+        ("src/lib.rs:3", "src/lib.rs", Some((3, 3))),
+        ("src/lib.rs:3-7", "src/lib.rs", Some((3, 7))),
+        ("a:b/lib.rs", "a:b/lib.rs", None),
+    ];
+
+    for &(test_file, path, lines) in EXAMPLES {
+        let (path_buf, range) = parse_selector(Path::new(test_file)).unwrap();
+        assert_eq!(Path::new(path), path_buf);
+        assert_eq!(lines.map(|(start, end)| start..=end), range);
+    }
+
+    for test_file in [
+        "src/lib.rs:0",
+        "src/lib.rs:7-3",
+        "src/lib.rs:-",
+        "src/lib.rs:3-",
+    ] {
+        assert!(parse_selector(Path::new(test_file)).is_err());
+    }
+}
@@ -22,11 +22,13 @@ impl<T> Default for Auto<T> {
     }
 }
 
-impl<T> ToImplementation for Auto<T>
+impl<T> Auto<T>
 where
-    T: Applicable + Display + IntoEnumIterator + ToImplementation,
+    T: Applicable + Clone + Display + IntoEnumIterator + ToImplementation,
 {
-    fn to_implementation(&self, context: &LightContext) -> Result<Option<Box<dyn Interface>>> {
+    /// Returns the framework that `self` refers to, searching for an applicable one if `self` is
+    /// `auto`
+    pub fn resolve(&self, context: &LightContext) -> Result<Option<T>> {
         match &self.0 {
             Union::Left(_) => {
                 let unflattened_frameworks = T::iter()
@@ -55,13 +57,22 @@ where
                         .join(", ")
                 );
 
-                if let Some(framework) = applicable_frameworks.into_iter().next() {
-                    framework.to_implementation(context)
-                } else {
-                    Ok(None)
-                }
+                Ok(applicable_frameworks.into_iter().next())
             }
-            Union::Right(framework) => framework.to_implementation(context),
+            Union::Right(framework) => Ok(Some(framework.clone())),
+        }
+    }
+}
+
+impl<T> ToImplementation for Auto<T>
+where
+    T: Applicable + Clone + Display + IntoEnumIterator + ToImplementation,
+{
+    fn to_implementation(&self, context: &LightContext) -> Result<Option<Box<dyn Interface>>> {
+        if let Some(framework) = self.resolve(context)? {
+            framework.to_implementation(context)
+        } else {
+            Ok(None)
         }
     }
 }
@@ -1,4 +1,4 @@
-use crate::{config, LightContext, Span};
+use crate::{config, Candidate, LightContext, Span};
 use anyhow::Result;
 use std::path::Path;
 use subprocess::{Exec, Popen};
@@ -31,7 +31,7 @@ pub trait Parse {
         context: &LightContext,
         config: &config::Toml,
         test_files: &[&Path],
-    ) -> Result<Vec<Span>>;
+    ) -> Result<Vec<Candidate>>;
 }
 
 pub type Postprocess = dyn Fn(&LightContext, Popen) -> Result<bool>;
@@ -61,7 +61,7 @@ impl<T: AsParse> Parse for T {
         context: &LightContext,
         config: &config::Toml,
         test_files: &[&Path],
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         self.as_parse_mut().parse(context, config, test_files)
     }
 }
@@ -0,0 +1,240 @@
+use crate::{util, Backup, LightContext};
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read, read_to_string, remove_file, write, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tempfile::NamedTempFile;
+
+const FILE_NAME: &str = "necessist.journal";
+
+// smoelius: Each line of the journal records a file that is about to be modified, and where the
+// file's original contents were copied.
+#[derive(Deserialize, Serialize)]
+struct Entry {
+    path: PathBuf,
+    backup: PathBuf,
+}
+
+/// A [`Backup`] whose existence is recorded in the journal. Dropping a `Journaled` restores the
+/// backed-up file and then removes its entry from the journal.
+pub struct Journaled {
+    path: PathBuf,
+    backup: Option<Backup>,
+    journal: PathBuf,
+    line: String,
+}
+
+impl Drop for Journaled {
+    fn drop(&mut self) {
+        let Some(mut backup) = self.backup.take() else {
+            return;
+        };
+        // smoelius: The journal is cleared only once the file is confirmed to be restored.
+        // Otherwise, the backup is kept so that `necessist restore` can put the file back.
+        if restore_from(&self.path, &backup).is_ok() {
+            drop(backup);
+            remove_entry(&self.journal, &self.line).unwrap_or_default();
+        } else {
+            backup.keep().unwrap_or_default();
+        }
+    }
+}
+
+fn restore_from(path: &Path, backup: &Backup) -> Result<()> {
+    let Some(tempfile) = backup.path() else {
+        bail!("Backup of {:?} is disabled", path);
+    };
+    let contents = read(tempfile)?;
+    write(path, &contents)?;
+    ensure!(read(path)? == contents, "Failed to restore {:?}", path);
+    Ok(())
+}
+
+// smoelius: Other files may still be modified, e.g., a test file while a framework's configuration
+// file is restored. So only this entry is removed, and the journal is removed once it is empty. The
+// journal is replaced by a rename so that the other entries survive if Necessist is killed.
+fn remove_entry(journal: &Path, line: &str) -> Result<()> {
+    let contents = read_to_string(journal)?;
+
+    let mut lines = contents.lines().collect::<Vec<_>>();
+    if let Some(i) = lines.iter().position(|other| *other == line) {
+        lines.remove(i);
+    }
+
+    if lines.is_empty() {
+        return remove_file(journal).map_err(Into::into);
+    }
+
+    let parent = journal
+        .parent()
+        .ok_or_else(|| anyhow!("Failed to get parent of {:?}", journal))?;
+    let mut tempfile = NamedTempFile::new_in(parent)?;
+    for line in lines {
+        writeln!(tempfile, "{line}")?;
+    }
+    tempfile.as_file().sync_all()?;
+    tempfile.persist(journal)?;
+
+    Ok(())
+}
+
+impl LightContext<'_> {
+    /// Backs up the file at `path` and records the backup in the journal. The file is restored
+    /// when the returned [`Journaled`] is dropped, or by `necessist restore` if Necessist is killed
+    /// first. A framework should use this for any file it modifies.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file could not be backed up or the journal could not be written.
+    pub fn journaled_backup(&self, path: &Path) -> Result<Journaled> {
+        let backup = Backup::new(path)?;
+        record(self, path, backup)
+    }
+}
+
+/// Records `backup` in the journal. The journal must be written before the backed-up file is
+/// modified, so that if Necessist is killed, `necessist restore` can put the file back.
+fn record(context: &LightContext, path: &Path, backup: Backup) -> Result<Journaled> {
+    let journal = context.root.join(FILE_NAME);
+
+    let Some(tempfile) = backup.path() else {
+        bail!("Backup of {:?} is disabled", path);
+    };
+
+    // smoelius: `std::fs::copy` does not sync the backup to disk. Without this, the backup could
+    // be lost on reboot while the journal survives.
+    File::open(tempfile)?.sync_all()?;
+
+    let entry = Entry {
+        path: path.to_path_buf(),
+        backup: tempfile.to_path_buf(),
+    };
+
+    let mut file = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(&journal)
+        .with_context(|| format!("Failed to open {journal:?}"))?;
+    let line = serde_json::to_string(&entry)?;
+    writeln!(file, "{line}")?;
+    file.sync_all()?;
+
+    Ok(Journaled {
+        path: path.to_path_buf(),
+        backup: Some(backup),
+        journal,
+        line,
+    })
+}
+
+/// Fails if the journal exists, i.e., if a previous run was interrupted before it could restore
+/// the files it modified.
+pub(crate) fn ensure_absent(context: &LightContext) -> Result<()> {
+    let journal = context.root.join(FILE_NAME);
+
+    ensure!(
+        !journal.try_exists()?,
+        "Found an unfinished journal at {:?}; a previous run appears to have been interrupted. \
+         Run `necessist restore` to restore the files it modified.",
+        journal
+    );
+
+    Ok(())
+}
+
+/// Restores every file recorded in the journal from its backup, and then removes the journal.
+pub(crate) fn restore(context: &LightContext) -> Result<()> {
+    let journal = context.root.join(FILE_NAME);
+
+    if !journal.try_exists()? {
+        (context.println)(&"Nothing to restore");
+        return Ok(());
+    }
+
+    let contents =
+        read_to_string(&journal).with_context(|| format!("Failed to read {journal:?}"))?;
+
+    // smoelius: A line could be incomplete if Necessist was killed while writing it. The file such
+    // a line refers to has not yet been modified.
+    let entries = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Entry>(line).ok())
+        .collect::<Vec<_>>();
+
+    for entry in &entries {
+        ensure!(
+            entry.backup.try_exists()?,
+            "Backup {:?} of {:?} does not exist",
+            entry.backup,
+            entry.path
+        );
+    }
+
+    // smoelius: Restore in reverse order so that if a file appears more than once, its earliest
+    // backup wins.
+    for entry in entries.iter().rev() {
+        std::fs::copy(&entry.backup, &entry.path)
+            .with_context(|| format!("Failed to restore {:?}", entry.path))?;
+        remove_file(&entry.backup)?;
+        (context.println)(&format!(
+            "Restored {}",
+            util::strip_current_dir(&entry.path).to_string_lossy()
+        ));
+    }
+
+    remove_file(&journal).map_err(Into::into)
+}
+
+#[cfg(test)]
+mod test {
+    use super::FILE_NAME;
+    use crate::{LightContext, Necessist, Session};
+    use std::{
+        fs::{read_to_string, write},
+        rc::Rc,
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn journal_is_kept_while_entries_remain() {
+        let tempdir = tempdir().unwrap();
+
+        let opts = Necessist::default();
+        let root = Rc::new(tempdir.path().to_path_buf());
+        let session = Session::default();
+        let context = LightContext {
+            opts: &opts,
+            root: &root,
+            println: &|_| {},
+            observer: &(),
+            session: &session,
+        };
+
+        let test_file = tempdir.path().join("test.rs");
+        let config_file = tempdir.path().join("config.toml");
+        let journal = tempdir.path().join(FILE_NAME);
+
+        write(&test_file, "test").unwrap();
+        write(&config_file, "config").unwrap();
+
+        let test_file_journaled = context.journaled_backup(&test_file).unwrap();
+        write(&test_file, "").unwrap();
+
+        let config_file_journaled = context.journaled_backup(&config_file).unwrap();
+        write(&config_file, "").unwrap();
+
+        drop(config_file_journaled);
+
+        assert_eq!("config", read_to_string(&config_file).unwrap());
+        assert_eq!("", read_to_string(&test_file).unwrap());
+        assert_eq!(1, read_to_string(&journal).unwrap().lines().count());
+
+        drop(test_file_journaled);
+
+        assert_eq!("test", read_to_string(&test_file).unwrap());
+        assert!(!journal.try_exists().unwrap());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::store::{Backend, Removal, Run};
+use anyhow::{Context, Result};
+use std::{
+    fs::{read_to_string, File, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+/// A storage backend that appends one JSON record per line to a file. The file is never rewritten;
+/// deletions are recorded as records of their own, and the file is replayed when opened.
+pub(crate) struct Jsonl {
+    file: File,
+    runs: Vec<Run>,
+    removals: Vec<Removal>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Record {
+    Run(Run),
+    Removal(Box<Removal>),
+    Deletion { run_id: String, span: String },
+}
+
+impl Jsonl {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut jsonl = Self {
+            file: OpenOptions::new().create(true).append(true).open(path)?,
+            runs: Vec::new(),
+            removals: Vec::new(),
+        };
+
+        let contents = read_to_string(path)?;
+
+        for (i, line) in contents.lines().enumerate() {
+            let record = serde_json::from_str::<Record>(line)
+                .with_context(|| format!("Failed to parse line {} of {:?}", i + 1, path))?;
+            jsonl.apply(record);
+        }
+
+        Ok(jsonl)
+    }
+
+    fn append(&mut self, record: Record) -> Result<()> {
+        let line = serde_json::to_string(&record)?;
+        writeln!(self.file, "{line}")?;
+        self.apply(record);
+        Ok(())
+    }
+
+    fn apply(&mut self, record: Record) {
+        match record {
+            Record::Run(run) => {
+                self.runs.push(run);
+            }
+            Record::Removal(removal) => {
+                self.remove(&removal.run_id, &removal.span);
+                self.removals.push(*removal);
+            }
+            Record::Deletion { run_id, span } => {
+                self.remove(&run_id, &span);
+            }
+        }
+    }
+
+    fn remove(&mut self, run_id: &str, span: &str) {
+        self.removals
+            .retain(|removal| removal.run_id != run_id || removal.span != span);
+    }
+}
+
+impl Backend for Jsonl {
+    fn runs(&mut self) -> Result<Vec<Run>> {
+        let mut runs = self.runs.clone();
+        runs.sort_by_key(|run| run.timestamp);
+        Ok(runs)
+    }
+
+    fn insert_run(&mut self, run: &Run) -> Result<()> {
+        self.append(Record::Run(run.clone()))
+    }
+
+    fn removals(&mut self, run_id: &str) -> Result<Vec<Removal>> {
+        Ok(self
+            .removals
+            .iter()
+            .filter(|removal| removal.run_id == run_id)
+            .cloned()
+            .collect())
+    }
+
+    fn insert_removal(&mut self, removal: &Removal) -> Result<()> {
+        self.append(Record::Removal(Box::new(removal.clone())))
+    }
+
+    fn delete_removal(&mut self, run_id: &str, span: &str) -> Result<()> {
+        self.append(Record::Deletion {
+            run_id: run_id.to_owned(),
+            span: span.to_owned(),
+        })
+    }
+}
@@ -10,43 +10,84 @@ use backup::Backup;
 #[doc(hidden)]
 pub use backup::Backup as __Backup;
 
+mod candidate;
+pub use candidate::{Candidate, Kind};
+
 #[cfg(feature = "clap")]
 pub mod cli;
 
+mod compare;
+
 pub mod config;
 
 mod core;
-use crate::core::Removal;
-pub use crate::core::{necessist, LightContext, Necessist};
+pub use crate::core::{
+    necessist, necessist_with_observer, ExitStatus, LightContext, Necessist, Removal, Subcommand,
+    Timing,
+};
+
+mod filter;
 
 #[cfg(all(unix, feature = "lock_root"))]
 mod flock;
 
 pub mod framework;
 
+mod journal;
+pub use journal::Journaled;
+
+mod jsonl;
+
+mod observer;
+pub use observer::Observer;
+
 mod offset_based_rewriter;
 
 mod offset_calculator;
 
 mod outcome;
-use outcome::Outcome;
+pub use outcome::Outcome;
+
+mod permalink;
 
 mod rewriter;
 use rewriter::Rewriter;
 
+mod sample;
+pub use sample::Sample;
+
+mod session;
+pub use session::Session;
+
+mod shard;
+pub use shard::{Shard, ShardBy};
+
 mod source_file;
 pub use source_file::SourceFile;
 
 mod span;
 pub use span::{Span, ToInternalSpan};
 
+#[cfg(feature = "sqlite")]
 mod sqlite;
 
+mod store;
+pub use store::Storage;
+
+mod summary;
+
+mod time_budget;
+pub use time_budget::TimeBudget;
+
 mod to_console_string;
 use to_console_string::ToConsoleString;
 
 pub mod util;
 
+mod vacuous;
+
 mod warn;
 use warn::note;
 pub use warn::{source_warn, warn, Flags as WarnFlags, Warning};
+
+mod watch;
@@ -0,0 +1,34 @@
+use crate::{Candidate, Removal, Warning};
+use anyhow::Result;
+use std::path::Path;
+
+/// Receives events as Necessist runs. Each method does nothing by default, so an implementation
+/// need only override the methods for the events it cares about.
+///
+/// Events are delivered regardless of `--quiet`, so a library user can suppress console output and
+/// still receive structured results.
+pub trait Observer {
+    /// Called once, after the test files have been parsed and the candidates to try have been
+    /// selected (e.g., by `--shard` or `--sample`).
+    fn on_candidates(&self, _candidates: &[Candidate]) {}
+
+    /// Called after a test file has been dry run. `result` is `Err` if the dry run failed, in which
+    /// case the test file's candidates are skipped.
+    fn on_dry_run(&self, _test_file: &Path, _result: &Result<()>) {}
+
+    /// Called before `candidate` is removed and the tests are run. Each call is followed by a call
+    /// to [`Observer::on_removal_finished`] for the same candidate.
+    fn on_removal_started(&self, _candidate: &Candidate) {}
+
+    /// Called after the tests have been run with `candidate` removed. `removal` is `None` if no
+    /// outcome could be determined (e.g., because the tests' output could not be interpreted), in
+    /// which case nothing is recorded for the candidate.
+    fn on_removal_finished(&self, _candidate: &Candidate, _removal: Option<&Removal>) {}
+
+    /// Called when a warning is emitted. `source` is the file, span, etc. that the warning
+    /// concerns, if any. Warnings silenced with `--allow` are not reported, and warnings denied
+    /// with `--deny` are returned as errors instead.
+    fn on_warning(&self, _warning: Warning, _source: Option<&str>, _msg: &str) {}
+}
+
+impl Observer for () {}
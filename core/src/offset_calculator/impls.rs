@@ -5,13 +5,18 @@
 
 use super::Interface;
 use proc_macro2::LineColumn;
-use std::str::{Chars, Split};
+use std::{rc::Rc, str::Chars};
 
+// smoelius: Rather than borrow the original string, the offset calculators share ownership of it.
+// Where the original `CachingOffsetCalculator` held a `Split` and a `Chars`, this one holds the
+// byte offset of the next line (`None` once the lines are exhausted), and the byte range of the
+// current line's unconsumed characters.
 #[derive(Debug)]
-pub struct CachingOffsetCalculator<'original> {
-    lines: Split<'original, char>,
+pub struct CachingOffsetCalculator {
+    original: Rc<str>,
+    next_line: Option<usize>,
     prefix: String,
-    chars: Option<Chars<'original>>,
+    chars: Option<(usize, usize)>,
     line_column: LineColumn,
     offset: usize,
     ascii: bool,
@@ -21,14 +26,15 @@ pub struct CachingOffsetCalculator<'original> {
 }
 
 #[derive(Debug)]
-pub struct StatelessOffsetCalculator<'original> {
-    original: &'original str,
+pub struct StatelessOffsetCalculator {
+    original: Rc<str>,
 }
 
-impl<'original> CachingOffsetCalculator<'original> {
-    pub fn new(original: &'original str) -> Self {
+impl CachingOffsetCalculator {
+    pub fn new(original: Rc<str>) -> Self {
         Self {
-            lines: original.split('\n'),
+            original,
+            next_line: Some(0),
             prefix: String::new(),
             chars: None,
             line_column: LineColumn { line: 1, column: 0 },
@@ -40,14 +46,14 @@ impl<'original> CachingOffsetCalculator<'original> {
     }
 }
 
-impl<'original> StatelessOffsetCalculator<'original> {
+impl StatelessOffsetCalculator {
     #[allow(dead_code)]
-    pub fn new(original: &'original str) -> Self {
+    pub fn new(original: Rc<str>) -> Self {
         Self { original }
     }
 }
 
-impl<'original> Interface for CachingOffsetCalculator<'original> {
+impl Interface for CachingOffsetCalculator {
     fn offset_from_line_column(&mut self, line_column: LineColumn) -> (usize, bool) {
         assert!(self.line_column.line == self.lines_and_offsets.len() + 1);
 
@@ -71,23 +77,23 @@ impl<'original> Interface for CachingOffsetCalculator<'original> {
             return (offset, ascii);
         }
 
-        let mut chars = self
-            .chars
-            .take()
-            .unwrap_or_else(|| self.lines.next().unwrap().chars());
+        let original = self.original.clone();
+
+        let (mut start, mut end) = self.chars.take().unwrap_or_else(|| self.next_line());
 
         if self.line_column.line < line_column.line {
-            self.push_lines(chars, line_column.line);
+            self.push_lines(original[start..end].chars(), line_column.line);
 
-            chars = self.lines.next().unwrap().chars();
+            (start, end) = self.next_line();
         }
 
         assert!(self.line_column.line >= line_column.line);
 
+        let mut chars = original[start..end].chars();
         let (prefix, offset, ascii) =
             Self::char_offset(&mut chars, line_column.column - self.line_column.column);
         self.prefix += &prefix;
-        self.chars = Some(chars);
+        self.chars = Some((end - chars.as_str().len(), end));
         self.offset += offset;
         self.ascii &= ascii;
 
@@ -97,7 +103,18 @@ impl<'original> Interface for CachingOffsetCalculator<'original> {
     }
 }
 
-impl<'original> CachingOffsetCalculator<'original> {
+impl CachingOffsetCalculator {
+    fn next_line(&mut self) -> (usize, usize) {
+        let start = self.next_line.unwrap();
+        if let Some(index) = self.original[start..].find('\n') {
+            self.next_line = Some(start + index + 1);
+            (start, start + index)
+        } else {
+            self.next_line = None;
+            (start, self.original.len())
+        }
+    }
+
     fn push_lines(&mut self, chars: Chars<'_>, one_based_index: usize) {
         let suffix = chars.collect::<String>();
 
@@ -113,8 +130,11 @@ impl<'original> CachingOffsetCalculator<'original> {
         }
         self.lines_and_offsets.push((line, self.offset));
 
+        let original = self.original.clone();
+
         while self.line_column.line < one_based_index {
-            let line = self.lines.next().unwrap();
+            let (start, end) = self.next_line();
+            let line = &original[start..end];
 
             self.offset += line.as_bytes().len() + 1;
             self.ascii &= line.chars().all(|ch| ch.is_ascii());
@@ -138,7 +158,7 @@ impl<'original> CachingOffsetCalculator<'original> {
     }
 }
 
-impl<'original> Interface for StatelessOffsetCalculator<'original> {
+impl Interface for StatelessOffsetCalculator {
     #[cfg_attr(
         dylint_lib = "misleading_variable_name",
         allow(misleading_variable_name)
@@ -2,6 +2,7 @@
 // https://github.com/smoelius/rustfmt_if_chain/blob/557c32c54b0e0f48da2d029a3a8f70db4c8dbf9b/src/offset_calculator/mod.rs
 
 use proc_macro2::LineColumn;
+use std::rc::Rc;
 
 mod impls;
 
@@ -15,25 +16,25 @@ pub trait Interface {
 }
 
 #[derive(Debug)]
-pub struct OffsetCalculator<'original> {
-    caching: CachingOffsetCalculator<'original>,
+pub struct OffsetCalculator {
+    caching: CachingOffsetCalculator,
 
     #[cfg(feature = "check-offsets")]
-    stateless: StatelessOffsetCalculator<'original>,
+    stateless: StatelessOffsetCalculator,
 }
 
-impl<'original> OffsetCalculator<'original> {
-    pub fn new(original: &'original str) -> Self {
+impl OffsetCalculator {
+    pub fn new(original: Rc<str>) -> Self {
         Self {
-            caching: CachingOffsetCalculator::new(original),
-
             #[cfg(feature = "check-offsets")]
-            stateless: StatelessOffsetCalculator::new(original),
+            stateless: StatelessOffsetCalculator::new(original.clone()),
+
+            caching: CachingOffsetCalculator::new(original),
         }
     }
 }
 
-impl<'original> Interface for OffsetCalculator<'original> {
+impl Interface for OffsetCalculator {
     fn offset_from_line_column(&mut self, line_column: LineColumn) -> (usize, bool) {
         let (offset, ascii) = self.caching.offset_from_line_column(line_column);
 
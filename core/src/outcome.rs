@@ -8,8 +8,9 @@ use std::str::FromStr;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
-#[derive(Clone, Copy, Debug, EnumIter, PartialEq)]
-pub(crate) enum Outcome {
+/// How the tests fared with a candidate removed
+#[derive(Clone, Copy, Debug, EnumIter, Eq, Hash, PartialEq)]
+pub enum Outcome {
     Nonbuildable,
     Failed,
     TimedOut,
@@ -32,7 +33,7 @@ impl FromStr for Outcome {
 }
 
 impl Outcome {
-    pub fn style(self) -> Style {
+    pub(crate) fn style(self) -> Style {
         match self {
             Outcome::Nonbuildable => Blue.normal(),
             Outcome::Failed => Green.normal(),
@@ -0,0 +1,298 @@
+use crate::{config, util, Span};
+use anyhow::{bail, Result};
+use git2::{Oid, Repository};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// URL templates for popular forges. A template's placeholders are described in the README's
+/// "Configuration files" section.
+const PRESETS: &[(&str, &str)] = &[
+    (
+        "bitbucket",
+        "{base_url}/src/{commit}/{path}#lines-{start_line}:{end_line}",
+    ),
+    (
+        "gitea",
+        "{base_url}/src/commit/{commit}/{path}#L{start_line}-L{end_line}",
+    ),
+    (
+        "github",
+        "{base_url}/blob/{commit}/{path}#L{start_line}-L{end_line}",
+    ),
+    (
+        "gitlab",
+        "{base_url}/-/blob/{commit}/{path}#L{start_line}-{end_line}",
+    ),
+    (
+        "sourcehut",
+        "{base_url}/tree/{commit}/item/{path}#L{start_line}-{end_line}",
+    ),
+];
+
+const PLACEHOLDERS: &[&str] = &["base_url", "commit", "path", "start_line", "end_line"];
+
+/// Produces links to spans, either within a forge's web interface or, if no remote could be found,
+/// within the local filesystem.
+pub(crate) struct Permalinks {
+    remote: Option<Remote>,
+}
+
+struct Remote {
+    repository: Repository,
+    base_url: String,
+    oid: Oid,
+    template: String,
+}
+
+impl Permalinks {
+    pub fn new(
+        config: &config::Toml,
+        repository: Option<Repository>,
+        oid: Option<Oid>,
+    ) -> Result<Self> {
+        let template = config
+            .url_template
+            .as_deref()
+            .map(resolve_template)
+            .transpose()?;
+
+        let remote = if let Some((repository, oid)) = repository.zip(oid) {
+            find_remote_url(&repository, config.url_remote.as_deref())?.map(|url| {
+                let base_url = base_url(&url);
+                let template = template.unwrap_or_else(|| infer_template(&base_url).to_owned());
+                Remote {
+                    repository,
+                    base_url,
+                    oid,
+                    template,
+                }
+            })
+        } else {
+            None
+        };
+
+        Ok(Self { remote })
+    }
+
+    pub fn url(&self, span: &Span) -> String {
+        let Some(remote) = &self.remote else {
+            return format!("file://{}", span.source_file.to_string_lossy());
+        };
+
+        #[allow(clippy::unwrap_used)]
+        let path = remote
+            .repository
+            .workdir()
+            .and_then(|path| util::strip_prefix(&span.source_file, path).ok())
+            .unwrap();
+
+        expand(
+            &remote.template,
+            &remote.base_url,
+            &remote.oid.to_string(),
+            &path.to_string_lossy(),
+            span.start.line,
+            span.end.line,
+        )
+    }
+}
+
+fn resolve_template(template: &str) -> Result<String> {
+    if let Some((_, preset)) = PRESETS.iter().find(|(name, _)| *name == template) {
+        return Ok((*preset).to_owned());
+    }
+
+    if !template.contains('{') {
+        bail!(
+            "Unknown URL template preset `{}`; expected one of: {}",
+            template,
+            PRESETS
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    for captures in PLACEHOLDER_RE.captures_iter(template) {
+        if !PLACEHOLDERS.contains(&&captures[1]) {
+            bail!(
+                "URL template contains unknown placeholder `{}`",
+                &captures[0]
+            );
+        }
+    }
+
+    Ok(template.to_owned())
+}
+
+// smoelius: If no remote is configured, prefer `origin`, but fall back to whichever remote comes
+// first, e.g., for clones that have only `upstream`. A configured remote that does not exist is an
+// error, as falling back would produce wrong links.
+fn find_remote_url(repository: &Repository, name: Option<&str>) -> Result<Option<String>> {
+    let remotes = repository.remotes()?;
+
+    let name = if let Some(name) = name {
+        if !remotes.iter().any(|remote| remote == Some(name)) {
+            bail!(
+                "Remote `{}` given by `url_remote` does not exist; remotes are: {}",
+                name,
+                remotes.iter().flatten().collect::<Vec<_>>().join(", ")
+            );
+        }
+        name
+    } else if remotes.iter().any(|remote| remote == Some("origin")) {
+        "origin"
+    } else if let Some(name) = remotes.iter().flatten().next() {
+        name
+    } else {
+        return Ok(None);
+    };
+
+    let remote = repository.find_remote(name)?;
+
+    Ok(remote.url().map(str::to_owned))
+}
+
+fn infer_template(base_url: &str) -> &'static str {
+    let host = HOST_RE
+        .captures(base_url)
+        .map(|captures| captures[1].to_lowercase())
+        .unwrap_or_default();
+
+    let name = if host.contains("gitlab") {
+        "gitlab"
+    } else if host.contains("bitbucket") {
+        "bitbucket"
+    } else if host.ends_with("sr.ht") {
+        "sourcehut"
+    } else if host.contains("gitea") || host.contains("codeberg") {
+        "gitea"
+    } else {
+        "github"
+    };
+
+    #[allow(clippy::unwrap_used)]
+    PRESETS
+        .iter()
+        .find_map(|(other, template)| {
+            if *other == name {
+                Some(*template)
+            } else {
+                None
+            }
+        })
+        .unwrap()
+}
+
+static SSH_RE: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new(r"^[^@/]*@([^:]*):(.*)$").unwrap()
+});
+
+static SSH_URL_RE: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new(r"^ssh://(?:[^@/]*@)?([^:/]*)(?::[0-9]*)?/(.*)$").unwrap()
+});
+
+static HOST_RE: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new(r"^[a-z]+://([^/:]*)").unwrap()
+});
+
+static PLACEHOLDER_RE: Lazy<Regex> = Lazy::new(|| {
+    #[allow(clippy::unwrap_used)]
+    Regex::new(r"\{([^}]*)\}").unwrap()
+});
+
+fn base_url(url: &str) -> String {
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    if let Some(captures) = SSH_URL_RE.captures(url).or_else(|| SSH_RE.captures(url)) {
+        assert!(captures.len() == 3);
+        format!("https://{}/{}", &captures[1], &captures[2])
+    } else {
+        url.to_owned()
+    }
+}
+
+fn expand(
+    template: &str,
+    base_url: &str,
+    commit: &str,
+    path: &str,
+    start_line: usize,
+    end_line: usize,
+) -> String {
+    PLACEHOLDER_RE
+        .replace_all(template, |captures: &regex::Captures| match &captures[1] {
+            "base_url" => base_url.to_owned(),
+            "commit" => commit.to_owned(),
+            "path" => path.to_owned(),
+            "start_line" => start_line.to_string(),
+            "end_line" => end_line.to_string(),
+            _ => captures[0].to_owned(),
+        })
+        .into_owned()
+}
+
+#[test]
+fn urls() {
+    const EXAMPLES: &[(&str, &str)] = &[
+        (
+            "git@github.com:trailofbits/necessist.git",
+            "https://github.com/trailofbits/necessist/blob/0123abc/src/lib.rs#L1-L2",
+        ),
+        (
+            "https://gitlab.example.com/group/project.git",
+            "https://gitlab.example.com/group/project/-/blob/0123abc/src/lib.rs#L1-2",
+        ),
+        (
+            "ssh://git@bitbucket.org:22/team/repo.git",
+            "https://bitbucket.org/team/repo/src/0123abc/src/lib.rs#lines-1:2",
+        ),
+        (
+            "https://codeberg.org/owner/repo",
+            "https://codeberg.org/owner/repo/src/commit/0123abc/src/lib.rs#L1-L2",
+        ),
+        (
+            "git@git.sr.ht:~owner/repo",
+            "https://git.sr.ht/~owner/repo/tree/0123abc/item/src/lib.rs#L1-2",
+        ),
+    ];
+
+    for (url, expected) in EXAMPLES {
+        let base_url = base_url(url);
+        let template = infer_template(&base_url);
+        assert_eq!(
+            *expected,
+            expand(template, &base_url, "0123abc", "src/lib.rs", 1, 2)
+        );
+    }
+
+    assert!(resolve_template("gitlab").is_ok());
+    assert!(resolve_template("https://example.com/{path}?line={start_line}").is_ok());
+    assert!(resolve_template("cgit").is_err());
+    assert!(resolve_template("https://example.com/{file}").is_err());
+
+    let tempdir = tempfile::tempdir().unwrap();
+    let repository = Repository::init(&tempdir).unwrap();
+    assert_eq!(None, find_remote_url(&repository, None).unwrap());
+    repository
+        .remote("upstream", "https://github.com/trailofbits/necessist.git")
+        .unwrap();
+    assert_eq!(
+        Some("https://github.com/trailofbits/necessist.git"),
+        find_remote_url(&repository, None).unwrap().as_deref()
+    );
+    assert_eq!(
+        Some("https://github.com/trailofbits/necessist.git"),
+        find_remote_url(&repository, Some("upstream"))
+            .unwrap()
+            .as_deref()
+    );
+    assert!(find_remote_url(&repository, Some("upstrem"))
+        .unwrap_err()
+        .to_string()
+        .contains("`upstrem` given by `url_remote` does not exist"));
+}
@@ -11,14 +11,14 @@ use std::cell::RefCell;
 #[derive(Debug)]
 pub(crate) struct Rewriter<'original, 'oc> {
     line_column: LineColumn,
-    offset_calculator: &'oc RefCell<OffsetCalculator<'original>>,
+    offset_calculator: &'oc RefCell<OffsetCalculator>,
     offset_based_rewriter: OffsetBasedRewriter<'original>,
 }
 
 impl<'original, 'oc> Rewriter<'original, 'oc> {
     pub fn new(
         original: &'original str,
-        offset_calculator: &'oc RefCell<OffsetCalculator<'original>>,
+        offset_calculator: &'oc RefCell<OffsetCalculator>,
     ) -> Self {
         Self {
             line_column: LineColumn { line: 1, column: 0 },
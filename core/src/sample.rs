@@ -0,0 +1,188 @@
+use crate::{Candidate, LightContext, Outcome, Removal, SourceFile};
+use anyhow::{anyhow, ensure, Error, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+// smoelius: The z-score for a 95% confidence interval.
+const Z: f64 = 1.96;
+
+/// How many of the candidates `--sample` tries: either a number of candidates or a percentage of
+/// them
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sample {
+    Count(usize),
+    Percent(f64),
+}
+
+impl FromStr for Sample {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(percent) = s.strip_suffix('%') {
+            let percent = percent.parse::<f64>()?;
+            ensure!(
+                percent > 0.0 && percent <= 100.0,
+                "Sample percentage must be greater than 0 and at most 100, but is {}",
+                percent
+            );
+            Ok(Self::Percent(percent))
+        } else {
+            let count = s
+                .parse::<usize>()
+                .map_err(|_| anyhow!("Sample `{}` is neither a number nor a percentage", s))?;
+            ensure!(count > 0, "Sample size must be greater than 0");
+            Ok(Self::Count(count))
+        }
+    }
+}
+
+impl Sample {
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    fn size(self, n: usize) -> usize {
+        match self {
+            Self::Count(count) => count.min(n),
+            Self::Percent(percent) => ((n as f64 * percent / 100.0).ceil() as usize).min(n),
+        }
+    }
+}
+
+// smoelius: SplitMix64 (https://prng.di.unimi.it/splitmix64.c) is used rather than a crate so that
+// a seed selects the same candidates regardless of dependency versions.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..n`. The slight bias toward smaller numbers is inconsequential here.
+    #[allow(clippy::cast_possible_truncation)]
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+pub(crate) fn random_seed() -> u64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    #[allow(clippy::cast_possible_truncation)]
+    SplitMix64(nanos as u64 ^ u64::from(std::process::id())).next_u64()
+}
+
+/// Keeps a random subset of `test_file_span_map`'s candidates, of the size `sample` specifies. The
+/// subset depends only on the candidates and `seed`.
+#[allow(clippy::mutable_key_type)]
+pub(crate) fn sample(
+    test_file_span_map: BTreeMap<SourceFile, Vec<Candidate>>,
+    sample: Sample,
+    seed: u64,
+) -> BTreeMap<SourceFile, Vec<Candidate>> {
+    let n = test_file_span_map.values().map(Vec::len).sum::<usize>();
+    let size = sample.size(n);
+
+    // smoelius: A partial Fisher-Yates shuffle. The first `size` indices are the sample.
+    let mut rng = SplitMix64(seed);
+    let mut indices = (0..n).collect::<Vec<_>>();
+    for i in 0..size {
+        let j = i + rng.below(n - i);
+        indices.swap(i, j);
+    }
+    let chosen = indices.into_iter().take(size).collect::<BTreeSet<_>>();
+
+    let mut i = 0;
+    test_file_span_map
+        .into_iter()
+        .filter_map(|(test_file, candidates)| {
+            let candidates = candidates
+                .into_iter()
+                .filter(|_| {
+                    let keep = chosen.contains(&i);
+                    i += 1;
+                    keep
+                })
+                .collect::<Vec<_>>();
+            if candidates.is_empty() {
+                None
+            } else {
+                Some((test_file, candidates))
+            }
+        })
+        .collect()
+}
+
+/// Prints the fraction of `removals` that passed, along with a 95% confidence interval (a Wilson
+/// score interval) for the fraction of all candidates whose removals would pass.
+pub(crate) fn estimate(context: &LightContext, removals: &[Removal]) {
+    if removals.is_empty() {
+        return;
+    }
+
+    let passed = removals
+        .iter()
+        .filter(|removal| removal.outcome == Outcome::Passed)
+        .count();
+
+    let (p, low, high) = wilson(passed, removals.len());
+
+    (context.println)(&format!(
+        "Estimated pass rate: {:.1}% (95% confidence interval: {:.1}% to {:.1}%), based on {} \
+         removal{}",
+        p * 100.0,
+        low * 100.0,
+        high * 100.0,
+        removals.len(),
+        if removals.len() == 1 { "" } else { "s" }
+    ));
+}
+
+/// Returns the fraction `passed / n`, along with the bounds of its 95% Wilson score interval. `n`
+/// must be nonzero.
+#[allow(clippy::cast_precision_loss)]
+fn wilson(passed: usize, n: usize) -> (f64, f64, f64) {
+    let n = n as f64;
+    let p = passed as f64 / n;
+
+    let denominator = 1.0 + Z * Z / n;
+    let center = (p + Z * Z / (2.0 * n)) / denominator;
+    let half_width = Z * (p * (1.0 - p) / n + Z * Z / (4.0 * n * n)).sqrt() / denominator;
+
+    (
+        p,
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::wilson;
+
+    #[test]
+    fn estimates_have_wilson_score_intervals() {
+        let (p, low, high) = wilson(5, 10);
+        assert!((p - 0.5).abs() < 1e-9);
+        assert!((low - 0.2366).abs() < 1e-4, "{low}");
+        assert!((high - 0.7634).abs() < 1e-4, "{high}");
+
+        let (p, low, high) = wilson(10, 10);
+        assert!((p - 1.0).abs() < 1e-9);
+        assert!((low - 0.7225).abs() < 1e-4, "{low}");
+        assert!((high - 1.0).abs() < 1e-9);
+
+        let (p, low, high) = wilson(0, 1);
+        assert!(p.abs() < 1e-9);
+        assert!(low.abs() < 1e-9);
+        assert!(high < 1.0);
+    }
+}
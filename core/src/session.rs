@@ -0,0 +1,18 @@
+use crate::{store::Store, warn, Removal, SourceFile, Warning};
+use once_cell::unsync::OnceCell;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+/// State that lives as long as one invocation of [`crate::necessist`]. Keeping this state here
+/// rather than in globals allows independent sessions to run in one process, e.g., on different
+/// roots.
+#[allow(clippy::type_complexity)]
+#[derive(Default)]
+pub struct Session {
+    pub(crate) source_files: RefCell<HashMap<PathBuf, SourceFile>>,
+    pub(crate) warning_state_map: RefCell<BTreeMap<Warning, warn::State>>,
+    pub(crate) store_and_past_removals: OnceCell<(RefCell<Option<Store>>, RefCell<Vec<Removal>>)>,
+}
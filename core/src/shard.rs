@@ -0,0 +1,86 @@
+use crate::{Candidate, SourceFile};
+use anyhow::{anyhow, ensure, Error, Result};
+use std::{collections::BTreeMap, str::FromStr};
+
+/// One of `count` disjoint parts of the candidates. `index` is 1-based.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Shard {
+    pub index: usize,
+    pub count: usize,
+}
+
+impl FromStr for Shard {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (index, count) = s
+            .split_once('/')
+            .ok_or_else(|| anyhow!("Shard `{}` is not of the form `i/n`", s))?;
+        let index = index.parse::<usize>()?;
+        let count = count.parse::<usize>()?;
+        ensure!(
+            1 <= index && index <= count,
+            "Shard `{}` must satisfy 1 <= i <= n",
+            s
+        );
+        Ok(Self { index, count })
+    }
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+/// How `--shard` divides the candidates: `File` keeps each test file's candidates together, so that
+/// each test file is dry run by only one shard; `Span` deals out candidates individually, which
+/// balances the shards more evenly.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[remain::sorted]
+pub enum ShardBy {
+    #[default]
+    File,
+    Span,
+}
+
+/// Keeps the part of `test_file_span_map` that belongs to `shard`. Because `test_file_span_map` is
+/// sorted, the result depends only on the candidates, so shards `1/n` through `n/n` cover every
+/// candidate exactly once.
+#[allow(clippy::mutable_key_type)]
+pub(crate) fn shard(
+    test_file_span_map: BTreeMap<SourceFile, Vec<Candidate>>,
+    shard: Shard,
+    shard_by: ShardBy,
+) -> BTreeMap<SourceFile, Vec<Candidate>> {
+    let belongs = |i: usize| i % shard.count == shard.index - 1;
+
+    match shard_by {
+        ShardBy::File => test_file_span_map
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, entry)| if belongs(i) { Some(entry) } else { None })
+            .collect(),
+        ShardBy::Span => {
+            let mut i = 0;
+            test_file_span_map
+                .into_iter()
+                .filter_map(|(test_file, candidates)| {
+                    let candidates = candidates
+                        .into_iter()
+                        .filter(|_| {
+                            let keep = belongs(i);
+                            i += 1;
+                            keep
+                        })
+                        .collect::<Vec<_>>();
+                    if candidates.is_empty() {
+                        None
+                    } else {
+                        Some((test_file, candidates))
+                    }
+                })
+                .collect()
+        }
+    }
+}
@@ -1,19 +1,15 @@
-use crate::{offset_calculator::OffsetCalculator, to_console_string::ToConsoleString, util};
+use crate::{
+    offset_calculator::OffsetCalculator, to_console_string::ToConsoleString, util, LightContext,
+};
 use anyhow::Result;
 use std::{
     cell::RefCell,
-    collections::HashMap,
     fs::read_to_string,
     ops::Deref,
     path::{Path, PathBuf},
     rc::Rc,
 };
 
-thread_local! {
-    static ROOT: RefCell<Option<Rc<PathBuf>>> = RefCell::new(None);
-    static SOURCE_FILES: RefCell<HashMap<PathBuf, SourceFile>> = RefCell::new(HashMap::new());
-}
-
 #[derive(Clone, Eq, PartialEq)]
 pub struct SourceFile {
     inner: Rc<Inner>,
@@ -22,8 +18,8 @@ pub struct SourceFile {
 struct Inner {
     root: Rc<PathBuf>,
     path: PathBuf,
-    contents: &'static str,
-    offset_calculator: RefCell<OffsetCalculator<'static>>,
+    contents: Rc<str>,
+    offset_calculator: RefCell<OffsetCalculator>,
 }
 
 impl Eq for Inner {}
@@ -35,40 +31,32 @@ impl PartialEq for Inner {
 }
 
 impl SourceFile {
-    pub fn new(root: Rc<PathBuf>, path: PathBuf) -> Result<Self> {
-        ROOT.with(|root_prev| {
-            let mut root_prev = root_prev.borrow_mut();
-
-            if let Some(root_prev) = root_prev.as_ref() {
-                assert_eq!(*root_prev, root);
-            } else {
-                assert!(root.is_absolute());
-                *root_prev = Some(root.clone());
-            }
-        });
-
-        assert!(path.starts_with(&*root));
-
-        SOURCE_FILES.with(|source_files| {
-            let mut source_files = source_files.borrow_mut();
-
-            if let Some(source_file) = source_files.get(&path) {
-                Ok(source_file.clone())
-            } else {
-                let contents = read_to_string(&path)?;
-                let leaked = Box::leak(contents.into_boxed_str());
-                let source_file = Self {
-                    inner: Rc::new(Inner {
-                        root,
-                        path: path.clone(),
-                        contents: leaked,
-                        offset_calculator: RefCell::new(OffsetCalculator::new(leaked)),
-                    }),
-                };
-                source_files.insert(path, source_file.clone());
-                Ok(source_file)
-            }
-        })
+    /// Returns the source file at `path`, which must be within `context.root`. A file's contents
+    /// are read only once per session, so that they reflect the file as it was before any removals.
+    /// (`--watch` discards a file's cached contents when the file is edited.)
+    pub fn new(context: &LightContext, path: PathBuf) -> Result<Self> {
+        let root = context.root;
+
+        assert!(root.is_absolute());
+        assert!(path.starts_with(&**root));
+
+        let mut source_files = context.session.source_files.borrow_mut();
+
+        if let Some(source_file) = source_files.get(&path) {
+            Ok(source_file.clone())
+        } else {
+            let contents = Rc::<str>::from(read_to_string(&path)?);
+            let source_file = Self {
+                inner: Rc::new(Inner {
+                    root: root.clone(),
+                    path: path.clone(),
+                    contents: contents.clone(),
+                    offset_calculator: RefCell::new(OffsetCalculator::new(contents)),
+                }),
+            };
+            source_files.insert(path, source_file.clone());
+            Ok(source_file)
+        }
     }
 
     fn relative_path(&self) -> &Path {
@@ -76,14 +64,13 @@ impl SourceFile {
         util::strip_prefix(&self.inner.path, &self.inner.root).unwrap()
     }
 
-    // smoelius: Leaking the file contents is a hack.
     #[must_use]
-    pub fn contents(&self) -> &'static str {
-        self.inner.contents
+    pub fn contents(&self) -> &str {
+        &self.inner.contents
     }
 
     #[must_use]
-    pub fn offset_calculator(&self) -> &RefCell<OffsetCalculator<'static>> {
+    pub fn offset_calculator(&self) -> &RefCell<OffsetCalculator> {
         &self.inner.offset_calculator
     }
 }
@@ -1,8 +1,8 @@
-use crate::{Backup, Rewriter, SourceFile, ToConsoleString};
+use crate::{Journaled, LightContext, Rewriter, SourceFile, ToConsoleString};
 use anyhow::{anyhow, Result};
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::{fs::OpenOptions, io::Write, path::PathBuf, rc::Rc};
+use std::{fs::OpenOptions, io::Write};
 
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
 pub struct Span {
@@ -33,36 +33,49 @@ static SPAN_RE: Lazy<Regex> = Lazy::new(|| {
     Regex::new(r"^([^:]*):([^:]*):([^-]*)-([^:]*):(.*)$").unwrap()
 });
 
+/// Splits a span's string representation into its path, start, and end, without reading the file
+/// the path refers to.
+pub(crate) fn parse_parts(
+    s: &str,
+) -> Result<(String, proc_macro2::LineColumn, proc_macro2::LineColumn)> {
+    let (source_file, start_line, start_column, end_line, end_column) = SPAN_RE
+        .captures(s)
+        .map(|captures| {
+            assert!(captures.len() == 6);
+            (
+                captures[1].to_owned(),
+                captures[2].to_owned(),
+                captures[3].to_owned(),
+                captures[4].to_owned(),
+                captures[5].to_owned(),
+            )
+        })
+        .ok_or_else(|| anyhow!("Span has unexpected format"))?;
+    let start_line = start_line.parse::<usize>()?;
+    let start_column = start_column.parse::<usize>()?;
+    let end_line = end_line.parse::<usize>()?;
+    let end_column = end_column.parse::<usize>()?;
+    Ok((
+        source_file,
+        proc_macro2::LineColumn {
+            line: start_line,
+            column: start_column - 1,
+        },
+        proc_macro2::LineColumn {
+            line: end_line,
+            column: end_column - 1,
+        },
+    ))
+}
+
 impl Span {
-    pub fn parse(root: &Rc<PathBuf>, s: &str) -> Result<Self> {
-        let (source_file, start_line, start_column, end_line, end_column) = SPAN_RE
-            .captures(s)
-            .map(|captures| {
-                assert!(captures.len() == 6);
-                (
-                    captures[1].to_owned(),
-                    captures[2].to_owned(),
-                    captures[3].to_owned(),
-                    captures[4].to_owned(),
-                    captures[5].to_owned(),
-                )
-            })
-            .ok_or_else(|| anyhow!("Span has unexpected format"))?;
-        let start_line = start_line.parse::<usize>()?;
-        let start_column = start_column.parse::<usize>()?;
-        let end_line = end_line.parse::<usize>()?;
-        let end_column = end_column.parse::<usize>()?;
-        let source_file = SourceFile::new(root.clone(), root.join(source_file))?;
+    pub fn parse(context: &LightContext, s: &str) -> Result<Self> {
+        let (source_file, start, end) = parse_parts(s)?;
+        let source_file = SourceFile::new(context, context.root.join(source_file))?;
         Ok(Self {
             source_file,
-            start: proc_macro2::LineColumn {
-                line: start_line,
-                column: start_column - 1,
-            },
-            end: proc_macro2::LineColumn {
-                line: end_line,
-                column: end_column - 1,
-            },
+            start,
+            end,
         })
     }
 
@@ -136,8 +149,8 @@ impl Span {
         Ok(text.to_owned())
     }
 
-    pub fn remove(&self) -> Result<(String, Backup)> {
-        let backup = Backup::new(&*self.source_file)?;
+    pub fn remove(&self, context: &LightContext) -> Result<(String, Journaled)> {
+        let journaled = context.journaled_backup(&self.source_file)?;
 
         let mut rewriter = Rewriter::new(
             self.source_file.contents(),
@@ -152,7 +165,7 @@ impl Span {
             .open(&*self.source_file)?;
         file.write_all(rewriter.contents().as_bytes())?;
 
-        Ok((text, backup))
+        Ok((text, journaled))
     }
 }
 
@@ -0,0 +1,647 @@
+use crate::{
+    config, jsonl, permalink::Permalinks, sample, warn, ExitStatus, Kind, LightContext, Outcome,
+    Span, Timing, WarnFlags, Warning,
+};
+use anyhow::{anyhow, bail, Result};
+use git2::{Repository, RepositoryOpenFlags};
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    fmt::Debug,
+    iter::empty,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const NECESSIST_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Where Necessist records its results
+#[derive(Clone, Copy, Debug, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
+#[remain::sorted]
+pub enum Storage {
+    Jsonl,
+    Sqlite,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        if cfg!(feature = "sqlite") {
+            Self::Sqlite
+        } else {
+            Self::Jsonl
+        }
+    }
+}
+
+impl Storage {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Jsonl => "necessist.jsonl",
+            Self::Sqlite => "necessist.db",
+        }
+    }
+
+    /// Returns an indefinite article and a noun describing the storage
+    fn description(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Jsonl => ("a", "JSON Lines database"),
+            Self::Sqlite => ("an", "sqlite database"),
+        }
+    }
+
+    /// Infers the storage of the database at `path` from its extension
+    fn from_extension(path: &Path) -> Option<Self> {
+        match path.extension().and_then(OsStr::to_str) {
+            Some("db") => Some(Self::Sqlite),
+            Some("jsonl") => Some(Self::Jsonl),
+            _ => None,
+        }
+    }
+
+    fn open(self, path: &Path) -> Result<Box<dyn Backend>> {
+        match self {
+            Self::Jsonl => Ok(Box::new(jsonl::Jsonl::open(path)?)),
+            #[cfg(feature = "sqlite")]
+            Self::Sqlite => Ok(Box::new(crate::sqlite::Sqlite::open(path)?)),
+            #[cfg(not(feature = "sqlite"))]
+            Self::Sqlite => bail!("Necessist was built without sqlite support"),
+        }
+    }
+}
+
+/// Operations that a storage backend must support. Spans, outcomes, etc. are passed to and from
+/// backends as strings.
+pub(crate) trait Backend {
+    /// Returns all runs, ordered from oldest to newest
+    fn runs(&mut self) -> Result<Vec<Run>>;
+    fn insert_run(&mut self, run: &Run) -> Result<()>;
+    fn removals(&mut self, run_id: &str) -> Result<Vec<Removal>>;
+    /// Inserts `removal`, replacing any removal with the same run id and span
+    fn insert_removal(&mut self, removal: &Removal) -> Result<()>;
+    fn delete_removal(&mut self, run_id: &str, span: &str) -> Result<()>;
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "sqlite", derive(diesel::Insertable, diesel::Queryable))]
+#[cfg_attr(feature = "sqlite", diesel(table_name = crate::sqlite::run))]
+pub(crate) struct Run {
+    pub id: String,
+    pub git_commit: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: i64,
+    pub necessist_version: Option<String>,
+    /// The `--sample` seed, or `None` if the run did not sample
+    pub seed: Option<i64>,
+    /// The `--time-budget` in seconds, or `None` if the run had none
+    pub time_budget: Option<i64>,
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[cfg_attr(feature = "sqlite", derive(diesel::Insertable, diesel::Queryable))]
+#[cfg_attr(feature = "sqlite", diesel(table_name = crate::sqlite::removal))]
+pub(crate) struct Removal {
+    pub span: String,
+    pub text: String,
+    pub outcome: String,
+    pub url: String,
+    pub test_name: Option<String>,
+    pub framework: Option<String>,
+    pub kind: Option<String>,
+    /// Milliseconds since the Unix epoch
+    pub started_at: Option<i64>,
+    /// Milliseconds since the Unix epoch
+    pub ended_at: Option<i64>,
+    /// Seconds
+    pub wall_time: Option<f64>,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub necessist_version: Option<String>,
+    pub run_id: String,
+    pub fingerprint: Option<String>,
+    pub should_panic: Option<bool>,
+}
+
+impl Removal {
+    fn into_internal_removal(self, context: &LightContext) -> Result<crate::Removal> {
+        let Removal {
+            span,
+            text,
+            outcome,
+            url: _,
+            test_name,
+            framework: _,
+            kind,
+            started_at,
+            ended_at,
+            wall_time,
+            exit_code,
+            signal,
+            necessist_version: _,
+            run_id: _,
+            fingerprint,
+            should_panic,
+        } = self;
+        let span = Span::parse(context, &span)?;
+        let outcome = outcome.parse::<Outcome>()?;
+        let kind = kind.as_deref().map(str::parse::<Kind>).transpose()?;
+        let timing = match (started_at, ended_at, wall_time) {
+            (Some(started_at), Some(ended_at), Some(wall_time)) => Some(Timing {
+                start: system_time_from_millis(started_at)?,
+                end: system_time_from_millis(ended_at)?,
+                wall_time: Duration::try_from_secs_f64(wall_time)?,
+            }),
+            _ => None,
+        };
+        let exit_status = match (exit_code, signal) {
+            (Some(exit_code), _) => Some(ExitStatus::Exited(u32::try_from(exit_code)?)),
+            (None, Some(signal)) => Some(ExitStatus::Signaled(u8::try_from(signal)?)),
+            (None, None) => None,
+        };
+        Ok(crate::Removal {
+            span,
+            text,
+            outcome,
+            test_name,
+            kind,
+            timing,
+            exit_status,
+            fingerprint,
+            should_panic,
+        })
+    }
+}
+
+pub(crate) struct Store {
+    backend: Box<dyn Backend>,
+    permalinks: Permalinks,
+    run_id: String,
+    seed: Option<u64>,
+}
+
+impl Store {
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    pub fn insert(&mut self, framework: &str, removal: &crate::Removal) -> Result<()> {
+        let crate::Removal {
+            span,
+            text,
+            outcome,
+            test_name,
+            kind,
+            timing,
+            exit_status,
+            fingerprint,
+            should_panic,
+        } = removal;
+
+        let (exit_code, signal) = match exit_status {
+            Some(ExitStatus::Exited(exit_code)) => (Some(i32::try_from(*exit_code)?), None),
+            Some(ExitStatus::Signaled(signal)) => (None, Some(i32::from(*signal))),
+            None => (None, None),
+        };
+
+        let removal = Removal {
+            span: span.to_string(),
+            text: text.clone(),
+            outcome: outcome.to_string(),
+            url: self.permalinks.url(span),
+            test_name: test_name.clone(),
+            framework: Some(framework.to_owned()),
+            kind: kind.as_ref().map(ToString::to_string),
+            started_at: timing
+                .map(|timing| millis_from_system_time(timing.start))
+                .transpose()?,
+            ended_at: timing
+                .map(|timing| millis_from_system_time(timing.end))
+                .transpose()?,
+            wall_time: timing.map(|timing| timing.wall_time.as_secs_f64()),
+            exit_code,
+            signal,
+            necessist_version: Some(NECESSIST_VERSION.to_owned()),
+            run_id: self.run_id.clone(),
+            fingerprint: fingerprint.clone(),
+            should_panic: *should_panic,
+        };
+
+        self.backend.insert_removal(&removal)
+    }
+
+    pub fn delete(&mut self, span: &Span) -> Result<()> {
+        self.backend.delete_removal(&self.run_id, &span.to_string())
+    }
+}
+
+pub(crate) fn init(
+    context: &LightContext,
+    root: &Path,
+    dump: bool,
+    reset: bool,
+    resume: bool,
+) -> Result<(Store, Vec<crate::Removal>)> {
+    let config = config::Toml::read(context, root)?;
+
+    let storage = storage(context, &config);
+    let path_buf = root.join(storage.file_name());
+    let (article, noun) = storage.description();
+
+    let exists = path_buf.try_exists()?;
+
+    let no_db_msg =
+        |flag: &str| format!("No {noun} to {flag} at {path_buf:?}; creating new database");
+
+    match (exists, dump, reset, resume) {
+        (true, false, false, false) => bail!(
+            "Found {} {} at {:?}; please pass either --reset or --resume",
+            article,
+            noun,
+            path_buf
+        ),
+        (false, true, _, _) => bail!("--dump was passed, but no {} found at {:?}", noun, path_buf),
+        (false, _, true, _) => warn(
+            context,
+            Warning::DatabaseDoesNotExist,
+            &no_db_msg("reset"),
+            WarnFlags::ONCE,
+        )?,
+        (false, _, _, true) => warn(
+            context,
+            Warning::DatabaseDoesNotExist,
+            &no_db_msg("resume"),
+            WarnFlags::ONCE,
+        )?,
+        _ => (),
+    }
+
+    let mut backend = storage.open(&path_buf)?;
+
+    let repository =
+        Repository::open_ext(root, RepositoryOpenFlags::empty(), empty::<&OsStr>()).ok();
+
+    let oid = repository
+        .as_ref()
+        .and_then(|repository| repository.refname_to_id("HEAD").ok());
+
+    // smoelius: `--reset` starts a new run. Earlier runs are kept so that they can be compared
+    // against.
+    let latest_run = if reset { None } else { backend.runs()?.pop() };
+
+    let (run_id, seed, removals) = if let Some(run) = latest_run {
+        #[allow(clippy::cast_sign_loss)]
+        let seed = run.seed.map(|seed| seed as u64);
+        if !dump {
+            check_resumed_seed(context, seed)?;
+        }
+        let removals = backend
+            .removals(&run.id)?
+            .into_iter()
+            .map(|removal| removal.into_internal_removal(context))
+            .collect::<Result<Vec<_>>>()?;
+        (run.id, seed, removals)
+    } else {
+        let run_id = new_run_id()?;
+        let seed = context
+            .opts
+            .sample
+            .map(|_| context.opts.seed.unwrap_or_else(sample::random_seed));
+        if !dump {
+            let run = Run {
+                id: run_id.clone(),
+                git_commit: oid.map(|oid| oid.to_string()),
+                timestamp: millis_from_system_time(SystemTime::now())?,
+                necessist_version: Some(NECESSIST_VERSION.to_owned()),
+                #[allow(clippy::cast_possible_wrap)]
+                seed: seed.map(|seed| seed as i64),
+                time_budget: context
+                    .opts
+                    .time_budget
+                    .map(|time_budget| i64::try_from(time_budget.0.as_secs()))
+                    .transpose()?,
+            };
+            backend.insert_run(&run)?;
+        }
+        (run_id, seed, Vec::new())
+    };
+
+    let permalinks = Permalinks::new(&config, repository, oid)?;
+
+    Ok((
+        Store {
+            backend,
+            permalinks,
+            run_id,
+            seed,
+        },
+        removals,
+    ))
+}
+
+// smoelius: A resumed run must try the same sample as the run it resumes, or the removals carried
+// over would not be a sample of the candidates tried.
+fn check_resumed_seed(context: &LightContext, seed: Option<u64>) -> Result<()> {
+    match (seed, context.opts.sample.is_some(), context.opts.seed) {
+        (None, true, _) => bail!(
+            "--sample was passed, but the run being resumed did not sample; pass --reset to start \
+             a new run"
+        ),
+        (Some(seed), false, _) => bail!(
+            "The run being resumed sampled with seed {}; pass the same --sample to resume it",
+            seed
+        ),
+        (Some(seed), _, Some(opts_seed)) if seed != opts_seed => bail!(
+            "--seed {} was passed, but the run being resumed used seed {}",
+            opts_seed,
+            seed
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Loads the removals of the run whose id is or begins with `run_id_prefix`. Returns the run's full
+/// id, and its removals' spans, texts, outcomes, and fingerprints.
+#[allow(clippy::type_complexity)]
+pub(crate) fn load_run(
+    context: &LightContext,
+    run_id_prefix: &str,
+) -> Result<(String, Vec<(String, String, Outcome, Option<String>)>)> {
+    let (run, removals) = load_raw_run(context, Some(run_id_prefix))?;
+
+    let removals = removals
+        .into_iter()
+        .map(|removal| {
+            let outcome = removal.outcome.parse::<Outcome>()?;
+            Ok((removal.span, removal.text, outcome, removal.fingerprint))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((run.id, removals))
+}
+
+/// Like [`load_run`], but returns the run and its removals as stored. If `run_id_prefix` is `None`,
+/// the most recent run is loaded.
+pub(crate) fn load_raw_run(
+    context: &LightContext,
+    run_id_prefix: Option<&str>,
+) -> Result<(Run, Vec<Removal>)> {
+    let config = config::Toml::read(context, context.root)?;
+
+    let storage = storage(context, &config);
+    let path_buf = context.root.join(storage.file_name());
+    let (_, noun) = storage.description();
+
+    if !path_buf.try_exists()? {
+        bail!("No {} found at {:?}", noun, path_buf);
+    }
+
+    let mut backend = storage.open(&path_buf)?;
+
+    let mut runs = backend.runs()?;
+
+    let Some(run_id_prefix) = run_id_prefix else {
+        let run = runs
+            .pop()
+            .ok_or_else(|| anyhow!("Found no runs in {:?}", path_buf))?;
+        let removals = backend.removals(&run.id)?;
+        return Ok((run, removals));
+    };
+
+    let matching = runs
+        .iter()
+        .filter(|run| run.id.starts_with(run_id_prefix))
+        .collect::<Vec<_>>();
+
+    let run = match matching.as_slice() {
+        [run] => *run,
+        _ => {
+            let run = runs.iter().find(|run| run.id == run_id_prefix);
+            let msg = if matching.is_empty() {
+                "Found no run"
+            } else {
+                "Found multiple runs"
+            };
+            run.ok_or_else(|| {
+                anyhow!(
+                    "{} matching `{}`; runs in {:?} are:{}",
+                    msg,
+                    run_id_prefix,
+                    path_buf,
+                    runs.iter().map(describe_run).collect::<String>()
+                )
+            })?
+        }
+    };
+
+    let removals = backend.removals(&run.id)?;
+
+    Ok((run.clone(), removals))
+}
+
+/// Combines the most recent runs of the databases at `inputs` (e.g., one per shard) into a single
+/// run in a new database at `output`. The merged run takes its id, git commit, etc. from the first
+/// input. A span that appears in more than one input is an error.
+pub(crate) fn merge(context: &LightContext, inputs: &[PathBuf], output: &Path) -> Result<()> {
+    let config = config::Toml::read(context, context.root)?;
+
+    // smoelius: A database's extension takes precedence over `--storage`, so that databases of
+    // different storages can be merged.
+    let open = |path: &Path| {
+        Storage::from_extension(path)
+            .unwrap_or_else(|| storage(context, &config))
+            .open(path)
+    };
+
+    if output.try_exists()? {
+        bail!("{:?} already exists", output);
+    }
+
+    let mut merged_run = None;
+    let mut removals = BTreeMap::<String, (&PathBuf, Removal)>::new();
+
+    for input in inputs {
+        if !input.try_exists()? {
+            bail!("No database found at {:?}", input);
+        }
+
+        let mut backend = open(input)?;
+
+        let run = backend
+            .runs()?
+            .pop()
+            .ok_or_else(|| anyhow!("Found no runs in {:?}", input))?;
+
+        for removal in backend.removals(&run.id)? {
+            if let Some((other, _)) = removals.get(&removal.span) {
+                bail!(
+                    "{} appears in both {:?} and {:?}",
+                    removal.span,
+                    other,
+                    input
+                );
+            }
+            removals.insert(removal.span.clone(), (input, removal));
+        }
+
+        merged_run.get_or_insert(run);
+    }
+
+    let run = merged_run.ok_or_else(|| anyhow!("No databases to merge"))?;
+
+    let mut backend = open(output)?;
+
+    backend.insert_run(&run)?;
+
+    let n_removals = removals.len();
+
+    for (_, (_, mut removal)) in removals {
+        removal.run_id = run.id.clone();
+        backend.insert_removal(&removal)?;
+    }
+
+    (context.println)(&format!(
+        "Merged {} removals from {} database{} into {:?}",
+        n_removals,
+        inputs.len(),
+        if inputs.len() == 1 { "" } else { "s" },
+        output
+    ));
+
+    Ok(())
+}
+
+// smoelius: `--storage` takes precedence over the configuration file.
+fn storage(context: &LightContext, config: &config::Toml) -> Storage {
+    context.opts.storage.or(config.storage).unwrap_or_default()
+}
+
+fn describe_run(run: &Run) -> String {
+    format!(
+        "\n    {} (commit: {}, timestamp: {})",
+        run.id,
+        run.git_commit.as_deref().unwrap_or("unknown"),
+        run.timestamp
+    )
+}
+
+// smoelius: The counter distinguishes runs started by the same process within the clock's
+// resolution.
+fn new_run_id() -> Result<String> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let duration = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+    Ok(format!(
+        "{:x}-{:x}-{:x}-{n:x}",
+        duration.as_secs(),
+        duration.subsec_nanos(),
+        process::id()
+    ))
+}
+
+fn millis_from_system_time(system_time: SystemTime) -> Result<i64> {
+    let millis = system_time.duration_since(UNIX_EPOCH)?.as_millis();
+    i64::try_from(millis).map_err(Into::into)
+}
+
+fn system_time_from_millis(millis: i64) -> Result<SystemTime> {
+    let millis = u64::try_from(millis)?;
+    UNIX_EPOCH
+        .checked_add(Duration::from_millis(millis))
+        .ok_or_else(|| anyhow!("Timestamp is out of range"))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::{new_run_id, Backend, Removal, Run};
+    use crate::{jsonl::Jsonl, LightContext, Necessist, Session, Storage};
+    use std::{cell::RefCell, path::Path, rc::Rc};
+    use tempfile::tempdir;
+
+    #[test]
+    fn run_ids_are_unique() {
+        assert_ne!(new_run_id().unwrap(), new_run_id().unwrap());
+    }
+
+    #[test]
+    fn backends_replace_removals_at_the_same_span() {
+        let tempdir = tempdir().unwrap();
+
+        #[allow(unused_mut)]
+        let mut backends: Vec<Box<dyn Backend>> = vec![Box::new(
+            Jsonl::open(&tempdir.path().join("necessist.jsonl")).unwrap(),
+        )];
+        #[cfg(feature = "sqlite")]
+        backends.push(Box::new(
+            crate::sqlite::Sqlite::open(&tempdir.path().join("necessist.db")).unwrap(),
+        ));
+
+        for mut backend in backends {
+            backend.insert_run(&run("a")).unwrap();
+            backend.insert_removal(&removal("a", 1, "failed")).unwrap();
+            backend.insert_removal(&removal("a", 1, "passed")).unwrap();
+
+            let removals = backend.removals("a").unwrap();
+            assert_eq!(1, removals.len());
+            assert_eq!("passed", removals[0].outcome);
+        }
+    }
+
+    pub(crate) fn run(id: &str) -> Run {
+        Run {
+            id: id.to_owned(),
+            git_commit: None,
+            timestamp: 0,
+            necessist_version: None,
+            seed: None,
+            time_budget: None,
+        }
+    }
+
+    pub(crate) fn removal(run_id: &str, line: usize, outcome: &str) -> Removal {
+        Removal {
+            span: format!("src/lib.rs:{line}:5-{line}:12"),
+            text: "n += 1;".to_owned(),
+            outcome: outcome.to_owned(),
+            url: String::new(),
+            test_name: None,
+            framework: None,
+            kind: None,
+            started_at: None,
+            ended_at: None,
+            wall_time: None,
+            exit_code: None,
+            signal: None,
+            necessist_version: None,
+            run_id: run_id.to_owned(),
+            fingerprint: None,
+            should_panic: None,
+        }
+    }
+
+    /// Calls `f` with a context rooted at `root`, and returns the lines `f` printed
+    pub(crate) fn with_context(
+        root: &Path,
+        storage: Storage,
+        f: impl FnOnce(&LightContext),
+    ) -> Vec<String> {
+        let opts = Necessist {
+            storage: Some(storage),
+            ..Default::default()
+        };
+        let root = Rc::new(root.to_path_buf());
+        let lines = RefCell::new(Vec::new());
+        let println = |msg: &dyn AsRef<str>| lines.borrow_mut().push(msg.as_ref().to_owned());
+        let session = Session::default();
+        f(&LightContext {
+            opts: &opts,
+            root: &root,
+            println: &println,
+            observer: &(),
+            session: &session,
+        });
+        lines.into_inner()
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{LightContext, Outcome, Removal, ToConsoleString};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::Duration,
+};
+use strum::IntoEnumIterator;
+
+#[derive(Default)]
+struct Counts {
+    counts: HashMap<Outcome, usize>,
+    n: usize,
+}
+
+impl Counts {
+    fn add(&mut self, outcome: Outcome) {
+        *self.counts.entry(outcome).or_default() += 1;
+        self.n += 1;
+    }
+
+    fn get(&self, outcome: Outcome) -> usize {
+        self.counts.get(&outcome).copied().unwrap_or_default()
+    }
+}
+
+impl std::fmt::Display for Counts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // smoelius: `Outcome::iter` lists `Passed` last, but it is the outcome of most interest.
+        let counts = Outcome::iter()
+            .rev()
+            .map(|outcome| format!("{} {}", self.get(outcome), outcome))
+            .collect::<Vec<_>>();
+        write!(f, "{}", counts.join(", "))
+    }
+}
+
+/// Prints counts per outcome (overall, per file, and per test), timing statistics, and the tests
+/// ranked by how many of their removals passed.
+pub(crate) fn summarize(context: &LightContext, removals: &[Removal]) {
+    let mut overall = Counts::default();
+    let mut per_file = BTreeMap::<String, Counts>::new();
+    let mut per_test = BTreeMap::<(String, String), Counts>::new();
+    let mut total = Duration::ZERO;
+    let mut n_timed = 0;
+
+    for removal in removals {
+        let file = removal.span.source_file.to_console_string();
+        let test_name = removal
+            .test_name
+            .clone()
+            .unwrap_or_else(|| String::from("<unknown>"));
+
+        overall.add(removal.outcome);
+        per_file
+            .entry(file.clone())
+            .or_default()
+            .add(removal.outcome);
+        per_test
+            .entry((file, test_name))
+            .or_default()
+            .add(removal.outcome);
+
+        if let Some(timing) = &removal.timing {
+            total += timing.wall_time;
+            n_timed += 1;
+        }
+    }
+
+    (context.println)(&format!(
+        "Summary: {} removal{} ({overall})",
+        overall.n,
+        if overall.n == 1 { "" } else { "s" }
+    ));
+
+    // smoelius: Removals read from databases that predate timings do not count toward the average.
+    if n_timed > 0 {
+        #[allow(clippy::cast_possible_truncation)]
+        let average = total / n_timed as u32;
+        (context.println)(&format!(
+            "Time: {:.1}s total, {:.1}s average per removal",
+            total.as_secs_f64(),
+            average.as_secs_f64()
+        ));
+    }
+
+    (context.println)(&"Per file:");
+    for (file, counts) in &per_file {
+        (context.println)(&format!("  {file}: {counts}"));
+    }
+
+    let mut ranked = per_test.into_iter().collect::<Vec<_>>();
+    ranked.sort_by(|(left_key, left), (right_key, right)| {
+        right
+            .get(Outcome::Passed)
+            .cmp(&left.get(Outcome::Passed))
+            .then_with(|| left_key.cmp(right_key))
+    });
+
+    (context.println)(&"Per test, ranked by passed removals:");
+    for ((file, test_name), counts) in &ranked {
+        (context.println)(&format!("  {file}: {test_name}: {counts}"));
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{anyhow, ensure, Error, Result};
+use std::{
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+/// How long a run may take, e.g., `90s`, `30m`, or `1h30m`. A bare number is a number of seconds.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct TimeBudget(pub Duration);
+
+impl FromStr for TimeBudget {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || anyhow!("Time budget `{}` is not of the form, e.g., `1h30m`", s);
+
+        if let Ok(secs) = s.parse::<u64>() {
+            ensure!(secs > 0, "Time budget must be greater than 0");
+            return Ok(Self(Duration::from_secs(secs)));
+        }
+
+        let mut secs = 0_u64;
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            let n_digits = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let (digits, unit_and_rest) = rest.split_at(n_digits);
+            let n = digits.parse::<u64>().map_err(|_| invalid())?;
+            let mut chars = unit_and_rest.chars();
+            let multiplier = match chars.next() {
+                Some('h') => 60 * 60,
+                Some('m') => 60,
+                Some('s') => 1,
+                _ => return Err(invalid()),
+            };
+            secs = n
+                .checked_mul(multiplier)
+                .and_then(|n| secs.checked_add(n))
+                .ok_or_else(|| anyhow!("Time budget `{}` is too large", s))?;
+            rest = chars.as_str();
+        }
+
+        ensure!(secs > 0, "Time budget must be greater than 0");
+
+        Ok(Self(Duration::from_secs(secs)))
+    }
+}
+
+impl TimeBudget {
+    /// Returns when a run that started at `start` must stop, or `None` if that time is too far in
+    /// the future to represent
+    pub(crate) fn deadline(self, start: Instant) -> Option<Instant> {
+        start.checked_add(self.0)
+    }
+}
+
+/// Returns true if `deadline` has been reached at `now`
+pub(crate) fn is_exhausted(deadline: Option<Instant>, now: Instant) -> bool {
+    deadline.map_or(false, |deadline| now >= deadline)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_exhausted, TimeBudget};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn time_budgets_are_parsed() {
+        for (s, secs) in [
+            ("90", 90),
+            ("90s", 90),
+            ("30m", 30 * 60),
+            ("1h30m", 90 * 60),
+        ] {
+            assert_eq!(
+                TimeBudget(Duration::from_secs(secs)),
+                s.parse::<TimeBudget>().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_time_budgets_are_rejected() {
+        for s in ["", "0", "0s", "1x", "h", "1h30", "99999999999999999999h"] {
+            assert!(s.parse::<TimeBudget>().is_err(), "`{s}` was accepted");
+        }
+        assert!("5124095576030432h"
+            .parse::<TimeBudget>()
+            .unwrap_err()
+            .to_string()
+            .contains("too large"));
+    }
+
+    #[test]
+    fn runs_stop_when_time_budget_is_exhausted() {
+        let start = Instant::now();
+        let deadline = TimeBudget(Duration::from_secs(60)).deadline(start);
+
+        assert!(!is_exhausted(None, start));
+        assert!(!is_exhausted(deadline, start));
+        assert!(!is_exhausted(deadline, start + Duration::from_secs(59)));
+        assert!(is_exhausted(deadline, start + Duration::from_secs(60)));
+        assert!(is_exhausted(deadline, start + Duration::from_secs(61)));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::{span, store, warn, LightContext, Outcome, WarnFlags, Warning};
+use anyhow::{ensure, Result};
+use std::collections::BTreeMap;
+
+const DEFAULT_THRESHOLD: f64 = 1.0;
+
+#[derive(Default)]
+struct Test {
+    /// The number of the test's removals that built
+    n: usize,
+    /// The spans and texts of the test's passed removals
+    passed: Vec<(String, String)>,
+}
+
+impl Test {
+    #[allow(clippy::cast_precision_loss)]
+    fn fraction_passed(&self) -> f64 {
+        self.passed.len() as f64 / self.n as f64
+    }
+}
+
+/// Reports the tests at least `threshold` of whose removals passed in the run identified by
+/// `run_id_prefix` (or the most recent run). Such tests likely assert nothing meaningful. The tests
+/// are ranked by the fraction of their removals that passed.
+pub(crate) fn vacuous(
+    context: &LightContext,
+    run_id_prefix: Option<&str>,
+    threshold: Option<f64>,
+) -> Result<()> {
+    let threshold = threshold.unwrap_or(DEFAULT_THRESHOLD);
+
+    ensure!(
+        threshold > 0.0 && threshold <= 1.0,
+        "Threshold must be greater than 0 and at most 1, but is {}",
+        threshold
+    );
+
+    let (run, removals) = store::load_raw_run(context, run_id_prefix)?;
+    let run_id = run.id;
+
+    // smoelius: A run that sampled or had a time budget may have tried only some of a test's
+    // candidates, and the fraction of those whose removals passed need not reflect the test.
+    if let Some(option) = partial_run_option(run.seed, run.time_budget) {
+        warn(
+            context,
+            Warning::RunIncomplete,
+            &format!(
+                "Run {run_id} used {option}, so it may not have tried all of a test's candidates, \
+                 and a test may be reported based on only some of them"
+            ),
+            WarnFlags::empty(),
+        )?;
+    }
+
+    let mut tests = BTreeMap::<(String, String), Test>::new();
+
+    // smoelius: Removals read from databases that predate test names cannot be attributed to a
+    // test, and so are ignored. So are nonbuildable removals, as they say nothing about what a test
+    // asserts.
+    for removal in removals {
+        let Some(test_name) = removal.test_name else {
+            continue;
+        };
+        let outcome = removal.outcome.parse::<Outcome>()?;
+        if outcome == Outcome::Nonbuildable {
+            continue;
+        }
+        let (path, _, _) = span::parse_parts(&removal.span)?;
+        let test = tests.entry((path, test_name)).or_default();
+        test.n += 1;
+        if outcome == Outcome::Passed {
+            test.passed.push((removal.span, removal.text));
+        }
+    }
+
+    let mut flagged = tests
+        .into_iter()
+        .filter(|(_, test)| test.fraction_passed() >= threshold)
+        .collect::<Vec<_>>();
+
+    flagged.sort_by(|(left_key, left), (right_key, right)| {
+        right
+            .fraction_passed()
+            .total_cmp(&left.fraction_passed())
+            .then_with(|| right.n.cmp(&left.n))
+            .then_with(|| left_key.cmp(right_key))
+    });
+
+    if flagged.is_empty() {
+        (context.println)(&format!(
+            "No tests in run {run_id} had at least {} of their removals pass",
+            percent(threshold)
+        ));
+        return Ok(());
+    }
+
+    (context.println)(&format!(
+        "Tests in run {run_id} that had at least {} of their removals pass:",
+        percent(threshold)
+    ));
+
+    for ((path, test_name), test) in &flagged {
+        (context.println)(&format!(
+            "    {path}: {test_name} ({} of {} passed)",
+            test.passed.len(),
+            test.n
+        ));
+        for (span, text) in &test.passed {
+            (context.println)(&format!("        {span}: `{text}`"));
+        }
+    }
+
+    Ok(())
+}
+
+fn partial_run_option(seed: Option<i64>, time_budget: Option<i64>) -> Option<&'static str> {
+    match (seed, time_budget) {
+        (Some(_), Some(_)) => Some("--sample and --time-budget"),
+        (Some(_), None) => Some("--sample"),
+        (None, Some(_)) => Some("--time-budget"),
+        (None, None) => None,
+    }
+}
+
+fn percent(fraction: f64) -> String {
+    format!("{}%", (fraction * 100.0).round())
+}
+
+#[cfg(test)]
+mod test {
+    use super::vacuous;
+    use crate::{
+        jsonl::Jsonl,
+        store::{
+            test::{removal, run, with_context},
+            Backend, Removal,
+        },
+        Storage,
+    };
+    use tempfile::tempdir;
+
+    #[test]
+    fn nonbuildable_removals_are_not_counted() {
+        let tempdir = tempdir().unwrap();
+
+        let mut backend = Jsonl::open(&tempdir.path().join("necessist.jsonl")).unwrap();
+
+        backend.insert_run(&run("a")).unwrap();
+        for (line, outcome) in [(1, "passed"), (2, "nonbuildable")] {
+            backend
+                .insert_removal(&Removal {
+                    test_name: Some("test".to_owned()),
+                    ..removal("a", line, outcome)
+                })
+                .unwrap();
+        }
+
+        assert_eq!(
+            [
+                "Tests in run a that had at least 100% of their removals pass:",
+                "    src/lib.rs: test (1 of 1 passed)",
+                "        src/lib.rs:1:5-1:12: `n += 1;`",
+            ]
+            .as_slice(),
+            with_context(tempdir.path(), Storage::Jsonl, |context| {
+                vacuous(context, None, None).unwrap();
+            })
+        );
+    }
+}
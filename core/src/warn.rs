@@ -7,7 +7,6 @@ use anyhow::{bail, Result};
 use bitflags::bitflags;
 use heck::ToKebabCase;
 use is_terminal::IsTerminal;
-use std::{collections::BTreeMap, sync::Mutex};
 
 // smoelius: `Warning` is part of Necessist's public API. Please try to follow the naming convention
 // of `what` (e.g., `Output`) followed by `why` (e.g., `Invalid`).
@@ -18,6 +17,7 @@ use std::{collections::BTreeMap, sync::Mutex};
 pub enum Warning {
     All,
     DatabaseDoesNotExist,
+    DoctestParsingFailed,
     DryRunFailed,
     FilesChanged,
     IgnoredFunctionsUnsupported,
@@ -26,6 +26,7 @@ pub enum Warning {
     ItMessageNotFound,
     ModulePathUnknown,
     OutputInvalid,
+    RunIncomplete,
     RunTestFailed,
 }
 
@@ -77,15 +78,13 @@ https://github.com/trailofbits/necessist/issues
 ";
 
 bitflags! {
-    struct State: u8 {
+    pub(crate) struct State: u8 {
         const ALLOW_MSG_EMITTED = 1 << 0;
         const BUG_MSG_EMITTED = 1 << 1;
         const WARNING_EMITTED = 1 << 2;
     }
 }
 
-static WARNING_STATE_MAP: Mutex<BTreeMap<Warning, State>> = Mutex::new(BTreeMap::new());
-
 #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
 fn warn_internal(
     context: &LightContext,
@@ -96,8 +95,7 @@ fn warn_internal(
 ) -> Result<()> {
     assert_ne!(warning, Warning::All);
 
-    #[allow(clippy::unwrap_used)]
-    let mut warning_state_map = WARNING_STATE_MAP.lock().unwrap();
+    let mut warning_state_map = context.session.warning_state_map.borrow_mut();
 
     let state = warning_state_map
         .entry(warning)
@@ -116,14 +114,24 @@ fn warn_internal(
         bail!(msg);
     }
 
-    if context.opts.quiet
-        || context.opts.allow.contains(&Warning::All)
+    if context.opts.allow.contains(&Warning::All)
         || context.opts.allow.contains(&warning)
         || (flags.contains(Flags::ONCE) && state.contains(State::WARNING_EMITTED))
     {
         return Ok(());
     }
 
+    context.observer.on_warning(
+        warning,
+        source.map(ToConsoleString::to_console_string).as_deref(),
+        &msg,
+    );
+
+    if context.opts.quiet {
+        state.insert(State::WARNING_EMITTED);
+        return Ok(());
+    }
+
     let allow_msg = if state.contains(State::ALLOW_MSG_EMITTED) {
         String::new()
     } else {
@@ -182,7 +190,8 @@ fn may_be_bug(warning: Warning) -> bool {
         | Warning::IgnoredMacrosUnsupported
         | Warning::IgnoredMethodsUnsupported
         | Warning::ItMessageNotFound
-        | Warning::OutputInvalid => false,
-        Warning::ModulePathUnknown | Warning::RunTestFailed => true,
+        | Warning::OutputInvalid
+        | Warning::RunIncomplete => false,
+        Warning::DoctestParsingFailed | Warning::ModulePathUnknown | Warning::RunTestFailed => true,
     }
 }
@@ -0,0 +1,83 @@
+use anyhow::{anyhow, Result};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::{
+    collections::BTreeSet,
+    path::PathBuf,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError},
+    time::Duration,
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// smoelius: Editors often write a file in several steps (e.g., truncate, then write, or write a
+// temporary file and then rename it). Events that arrive within `DEBOUNCE_INTERVAL` of one another
+// are treated as one change.
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(250);
+
+pub(crate) struct Watcher {
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<notify::Result<Event>>,
+    paths: BTreeSet<PathBuf>,
+}
+
+impl Watcher {
+    /// Watches `paths`. Their parent directories are watched rather than the files themselves, so
+    /// that a file replaced by an editor continues to be watched.
+    pub fn new(paths: BTreeSet<PathBuf>) -> Result<Self> {
+        let (sender, receiver) = channel();
+
+        let mut watcher = notify::recommended_watcher(sender)?;
+
+        let parents = paths
+            .iter()
+            .filter_map(|path| path.parent())
+            .collect::<BTreeSet<_>>();
+
+        for parent in parents {
+            watcher.watch(parent, RecursiveMode::NonRecursive)?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver,
+            paths,
+        })
+    }
+
+    /// Blocks until at least one watched path changes, and returns the paths that changed. Returns
+    /// `None` if `stop` returns true first.
+    pub fn wait(&self, stop: impl Fn() -> bool) -> Result<Option<BTreeSet<PathBuf>>> {
+        let mut changed = BTreeSet::new();
+
+        loop {
+            let timeout = if changed.is_empty() {
+                POLL_INTERVAL
+            } else {
+                DEBOUNCE_INTERVAL
+            };
+
+            match self.receiver.recv_timeout(timeout) {
+                Ok(result) => {
+                    let event = result?;
+                    changed.extend(
+                        event
+                            .paths
+                            .into_iter()
+                            .filter(|path| self.paths.contains(path)),
+                    );
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    if !changed.is_empty() {
+                        return Ok(Some(changed));
+                    }
+                    if stop() {
+                        return Ok(None);
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(anyhow!("File watcher disconnected"));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,17 @@
+#[cfg(test)]
+mod test {
+    use std::{env::var, path::Path};
+
+    #[test]
+    fn cargo_env_is_set() {
+        let mut n = 0;
+        n += 1;
+        assert!(Path::new(&var("CARGO").unwrap()).exists());
+        assert_eq!(
+            env!("CARGO_MANIFEST_DIR"),
+            var("CARGO_MANIFEST_DIR").unwrap()
+        );
+        assert_eq!(env!("CARGO_PKG_NAME"), var("CARGO_PKG_NAME").unwrap());
+        assert_eq!(env!("CARGO_PKG_VERSION"), var("CARGO_PKG_VERSION").unwrap());
+    }
+}
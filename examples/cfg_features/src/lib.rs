@@ -0,0 +1,17 @@
+#[cfg(feature = "passed")]
+#[test]
+fn passed() {
+    let mut n = 0;
+    n += 1;
+    noop();
+}
+
+fn noop() {}
+
+#[cfg(feature = "failed")]
+#[test]
+fn failed() {
+    let mut n = 0;
+    n += 1;
+    assert!(n >= 1);
+}
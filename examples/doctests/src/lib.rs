@@ -0,0 +1,21 @@
+//! ```
+//! let mut n = 0;
+//! n += 1;
+//! assert_eq!(n, 1);
+//! ```
+
+/// Adds one to `n`.
+///
+/// ```
+/// let mut n = 0;
+/// n = doctests::increment(n);
+/// doctests::increment(n);
+/// assert_eq!(n, 1);
+/// ```
+///
+/// ```text
+/// n = increment(n);
+/// ```
+pub fn increment(n: u32) -> u32 {
+    n + 1
+}
@@ -0,0 +1,16 @@
+#[test]
+#[ignore]
+fn ignored() {
+    let mut n = 0;
+    n += 1;
+    assert_eq!(1, n);
+}
+
+#[test]
+#[ignore = "slow"]
+#[should_panic(expected = "index out of bounds")]
+fn ignored_should_panic() {
+    let mut xs = vec![1];
+    xs.clear();
+    let _ = xs[0];
+}
@@ -0,0 +1,8 @@
+fn main() {}
+
+#[test]
+fn example() {
+    let mut n = 0;
+    n += 1;
+    assert_eq!(1, n);
+}
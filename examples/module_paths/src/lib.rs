@@ -0,0 +1,6 @@
+#[path = "other/renamed.rs"]
+mod helpers;
+
+mod outer {
+    mod inner;
+}
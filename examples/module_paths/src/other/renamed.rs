@@ -0,0 +1,6 @@
+#[test]
+fn path_attribute() {
+    let mut n = 0;
+    n += 1;
+    assert_eq!(1, n);
+}
@@ -0,0 +1,6 @@
+#[test]
+fn inline_module() {
+    let mut n = 0;
+    n += 1;
+    assert_eq!(1, n);
+}
@@ -0,0 +1,27 @@
+pub fn double(x: u32) -> u32 {
+    x.wrapping_mul(2)
+}
+
+#[cfg(test)]
+mod test {
+    use super::double;
+    use rstest::rstest;
+    use test_case::test_case;
+
+    #[test_case(1 ; "one")]
+    #[test_case(2 ; "two")]
+    fn test_case_double(x: u32) {
+        let mut y = double(x);
+        y -= x;
+        assert_eq!(x, y);
+    }
+
+    #[rstest]
+    #[case(1)]
+    #[case(2)]
+    fn rstest_double(#[case] x: u32) {
+        let mut y = double(x);
+        y -= x;
+        assert_eq!(x, y);
+    }
+}
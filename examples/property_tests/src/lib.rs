@@ -0,0 +1,33 @@
+pub fn double(x: u32) -> u32 {
+    x.wrapping_mul(2)
+}
+
+#[cfg(test)]
+mod test {
+    use super::double;
+    use proptest::prelude::*;
+    use quickcheck::quickcheck;
+
+    proptest! {
+        #![proptest_config(ProptestConfig {
+            failure_persistence: None,
+            ..ProptestConfig::default()
+        })]
+
+        #[test]
+        fn proptest_double(x in 0..1000u32) {
+            let mut y = double(x);
+            y -= x;
+            prop_assert_eq!(x, y);
+        }
+    }
+
+    quickcheck! {
+        fn quickcheck_double(x: u32) -> bool {
+            let x = x % 1000;
+            let mut y = double(x);
+            y -= x;
+            x == y
+        }
+    }
+}
@@ -0,0 +1,16 @@
+#[test]
+#[should_panic]
+fn should_panic() {
+    let mut xs = vec![1];
+    xs.push(2);
+    xs.clear();
+    let _ = xs[0];
+}
+
+#[test]
+#[ignore]
+fn ignored() {
+    let mut n = 0;
+    n += 1;
+    assert_eq!(1, n);
+}
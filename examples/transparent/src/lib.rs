@@ -0,0 +1,31 @@
+struct Client;
+
+impl Client {
+    fn connect(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    async fn connect_async(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fn setup() -> Result<(), String> {
+    Ok(())
+}
+
+#[test]
+fn try_operator() -> Result<(), String> {
+    setup()?;
+    Client.connect()?;
+    Ok(())
+}
+
+#[test]
+fn unwrap() {
+    setup().unwrap();
+    Client.connect().expect("failed to connect");
+    let _ = async {
+        Client.connect_async().await.unwrap();
+    };
+}
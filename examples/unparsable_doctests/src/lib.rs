@@ -0,0 +1,34 @@
+/// Adds one to `n`.
+///
+/// ```
+/// let mut n = 0;
+/// n = unparsable_doctests::increment(n);
+/// assert_eq!(n, 1);
+/// ```
+pub fn increment(n: u32) -> u32 {
+    n + 1
+}
+
+// smoelius: Each doctest parses on its own. But this one's inner attribute follows the previous
+// doctest's code in the file of doctests that Necessist builds, and so that file does not parse.
+
+/// Subtracts one from `n`.
+///
+/// ```
+/// #![allow(unused_mut)]
+/// fn main() {
+///     let mut n = 1;
+///     n = unparsable_doctests::decrement(n);
+///     assert_eq!(n, 0);
+/// }
+/// ```
+pub fn decrement(n: u32) -> u32 {
+    n - 1
+}
+
+#[test]
+fn test() {
+    let mut n = 0;
+    n = increment(n);
+    assert_eq!(n, 1);
+}
@@ -0,0 +1,19 @@
+pub mod test_utils {
+    pub fn setup() {}
+    pub fn teardown() {}
+}
+
+#[cfg(test)]
+mod test {
+    use crate::test_utils::*;
+    use std::assert_eq as check;
+
+    #[test]
+    fn aliases() {
+        let mut n = 0;
+        setup();
+        n += 1;
+        check!(1, n);
+        teardown();
+    }
+}
@@ -2,9 +2,8 @@ use super::{ts, OutputAccessors, OutputStrippedOfAnsiScapes, ParseAdapter, Parse
 use anyhow::{anyhow, Result};
 use log::debug;
 use necessist_core::{
-    __Backup as Backup,
     framework::{Interface, Postprocess},
-    LightContext, Span,
+    Candidate, Journaled, LightContext, Span,
 };
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -56,7 +55,7 @@ impl ParseHigh for AnchorTs {
         context: &LightContext,
         config: &necessist_core::config::Toml,
         test_files: &[&Path],
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         self.mocha_adapter.parse(context, config, test_files)
     }
 }
@@ -67,7 +66,7 @@ impl RunHigh for AnchorTs {
 
         self.check(context, test_file)?;
 
-        let _backup = self.patch_anchor_toml(test_file, false)?;
+        let _backup = self.patch_anchor_toml(context, test_file, false)?;
 
         let command = command_to_run_test(context);
 
@@ -84,7 +83,7 @@ impl RunHigh for AnchorTs {
             return Ok(None);
         }
 
-        let backup = self.patch_anchor_toml(&span.source_file, false)?;
+        let backup = self.patch_anchor_toml(context, &span.source_file, false)?;
 
         let command = command_to_run_test(context);
 
@@ -93,7 +92,7 @@ impl RunHigh for AnchorTs {
         Ok(exec_and_postprocess.map(|(exec, postprocess)| {
             let postprocess: Box<Postprocess> = Box::new(move |context, popen| {
                 // smoelius: Ensure `backup` hasn't been dropped yet;
-                let _: &Backup = &backup;
+                let _: &Journaled = &backup;
                 if let Some(postprocess) = &postprocess {
                     postprocess(context, popen)
                 } else {
@@ -107,7 +106,7 @@ impl RunHigh for AnchorTs {
 
 impl AnchorTs {
     fn check(&self, context: &LightContext, test_file: &Path) -> Result<()> {
-        let _backup = self.patch_anchor_toml(test_file, true)?;
+        let _backup = self.patch_anchor_toml(context, test_file, true)?;
 
         let mut command = command_to_run_test(context);
 
@@ -120,8 +119,13 @@ impl AnchorTs {
         Ok(())
     }
 
-    fn patch_anchor_toml(&self, test_file: &Path, check: bool) -> Result<Backup> {
-        let backup = Backup::new(&self.anchor_toml)?;
+    fn patch_anchor_toml(
+        &self,
+        context: &LightContext,
+        test_file: &Path,
+        check: bool,
+    ) -> Result<Journaled> {
+        let backup = context.journaled_backup(&self.anchor_toml)?;
 
         let mut document = self.document.clone();
 
@@ -4,7 +4,7 @@ use super::{
 };
 use anyhow::{anyhow, Result};
 use if_chain::if_chain;
-use necessist_core::{util, LightContext, LineColumn, SourceFile, Span};
+use necessist_core::{util, Candidate, LightContext, LineColumn, SourceFile, Span};
 use solang_parser::pt::{CodeLocation, Expression, Identifier, Loc, SourceUnit, Statement};
 use std::{
     cell::RefCell, collections::BTreeMap, convert::Infallible, fs::read_to_string, path::Path,
@@ -183,7 +183,7 @@ impl ParseLow for Foundry {
         generic_visitor: GenericVisitor<'_, '_, '_, 'ast, Self>,
         storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
         file: &'ast <Self::Types as AbstractTypes>::File,
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         visit(generic_visitor, storage, &file.1)
     }
 
@@ -386,7 +386,8 @@ impl RunLow for Foundry {
         &self,
         context: &LightContext,
         span: &Span,
-    ) -> (Command, Vec<String>, Option<(ProcessLines, String)>) {
+        _build_stdout: &[u8],
+    ) -> Result<(Command, Vec<String>, Option<(ProcessLines, String)>)> {
         #[allow(clippy::expect_used)]
         let test_name = self
             .span_test_name_map
@@ -397,7 +398,7 @@ impl RunLow for Foundry {
         let mut command = Self::test_command(context, &span.source_file);
         command.args(["--match-test", &test_name]);
 
-        (
+        Ok((
             command,
             Vec::new(),
             Some((
@@ -407,7 +408,7 @@ impl RunLow for Foundry {
                 ),
                 test_name,
             )),
-        )
+        ))
     }
 }
 
@@ -1,6 +1,6 @@
 use super::{AbstractTypes, MaybeNamed, Named, ParseLow, Spanned};
 use if_chain::if_chain;
-use necessist_core::{config, LightContext, SourceFile, Span};
+use necessist_core::{config, Candidate, Kind, LightContext, SourceFile, Span};
 use paste::paste;
 use std::cell::RefCell;
 
@@ -10,11 +10,12 @@ pub struct GenericVisitor<'context, 'config, 'framework, 'ast, T: ParseLow + ?Si
     pub framework: &'framework mut T,
     pub source_file: SourceFile,
     pub test_name: Option<String>,
+    pub test_should_panic: bool,
     pub last_statement_in_test: Option<<T::Types as AbstractTypes>::Statement<'ast>>,
     pub n_statement_leaves_visited: usize,
     pub n_before: Vec<usize>,
     pub call_statement: Option<<T::Types as AbstractTypes>::Statement<'ast>>,
-    pub spans_visited: Vec<Span>,
+    pub spans_visited: Vec<Candidate>,
 }
 
 /// `call_info` return values. See that method for details.
@@ -29,6 +30,7 @@ struct VisitMaybeMacroCallArgs<'ast, 'storage, 'span, T: ParseLow> {
     storage: &'storage RefCell<<T::Types as AbstractTypes>::Storage<'ast>>,
     span: &'span Span,
     is_ignored_as_call: bool,
+    is_macro_call: bool,
     is_method_call: bool,
     is_ignored_as_method_call: bool,
 }
@@ -51,7 +53,16 @@ macro_rules! visit_maybe_macro_call {
                         if !$args.is_ignored_as_call {
                             let span = statement.span(&$this.source_file);
                             if $this.framework.on_candidate_found($this.context, $args.storage, &test_name, &span) {
-                                $this.spans_visited.push(span);
+                                $this.spans_visited.push(Candidate {
+                                    span,
+                                    test_name: test_name.clone(),
+                                    kind: if $args.is_macro_call {
+                                        Kind::Macro
+                                    } else {
+                                        Kind::Statement
+                                    },
+                                    should_panic: $this.test_should_panic,
+                                });
                             }
                         }
                     }
@@ -60,7 +71,12 @@ macro_rules! visit_maybe_macro_call {
                     // ignored as well.
                     if !$args.is_ignored_as_call && $args.is_method_call && !$args.is_ignored_as_method_call {
                         if $this.framework.on_candidate_found($this.context, $args.storage, &test_name, &$args.span) {
-                            $this.spans_visited.push($args.span.clone());
+                            $this.spans_visited.push(Candidate {
+                                span: $args.span.clone(),
+                                test_name: test_name.clone(),
+                                kind: Kind::MethodCall,
+                                should_panic: $this.test_should_panic,
+                            });
                         }
                     }
 
@@ -82,7 +98,7 @@ macro_rules! visit_call_post {
 impl<'context, 'config, 'framework, 'ast, T: ParseLow>
     GenericVisitor<'context, 'config, 'framework, 'ast, T>
 {
-    pub fn spans_visited(self) -> Vec<Span> {
+    pub fn spans_visited(self) -> Vec<Candidate> {
         self.spans_visited
     }
 
@@ -100,6 +116,7 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
 
         assert!(self.test_name.is_none());
         self.test_name = Some(name);
+        self.test_should_panic = self.framework.test_should_panic(storage, test);
 
         let statements = self.framework.test_statements(storage, test);
 
@@ -125,6 +142,7 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
         let name = test.name();
         assert!(self.test_name == Some(name));
         self.test_name = None;
+        self.test_should_panic = false;
     }
 
     pub fn visit_statement(
@@ -176,7 +194,12 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
                     .framework
                     .on_candidate_found(self.context, storage, test_name, &span)
                 {
-                    self.spans_visited.push(span);
+                    self.spans_visited.push(Candidate {
+                        span,
+                        test_name: test_name.clone(),
+                        kind: Kind::Statement,
+                        should_panic: self.test_should_panic,
+                    });
                 }
             }
         }
@@ -201,6 +224,7 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
                     span: &call_info.span,
                     is_ignored_as_call: (!inner_most_call_info.is_method && inner_most_call_info.is_ignored)
                         || (!inner_most_call_info.is_nested && call_info.is_ignored),
+                    is_macro_call: false,
                     is_method_call: true,
                     is_ignored_as_method_call: call_info.is_ignored
                 })
@@ -208,13 +232,14 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
         } else {
             let is_ignored_as_call = call
                 .name()
-                .map_or(false, |name| self.config.is_ignored_function(&name));
+                .map_or(false, |name| self.is_ignored_function(storage, &name));
             visit_maybe_macro_call! {
                 self,
                 (VisitMaybeMacroCallArgs::<'_, '_, '_, T> {
                     storage,
                     span: &call_span,
                     is_ignored_as_call,
+                    is_macro_call: false,
                     is_method_call: false,
                     is_ignored_as_method_call: false
                 })
@@ -245,7 +270,8 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
             (VisitMaybeMacroCallArgs::<'_, '_, '_, T> {
                 storage,
                 span: &macro_call.span(&self.source_file),
-                is_ignored_as_call: self.config.is_ignored_macro(&name),
+                is_ignored_as_call: self.is_ignored_macro(storage, &name),
+                is_macro_call: true,
                 is_method_call: false,
                 is_ignored_as_method_call: false
             })
@@ -380,7 +406,7 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
                     let name = call.name();
                     let is_ignored = name
                         .as_ref()
-                        .map_or(false, |name| self.config.is_ignored_function(name));
+                        .map_or(false, |name| self.is_ignored_function(storage, name));
                     CallInfo {
                         span: call.span(&self.source_file),
                         is_method: false,
@@ -392,7 +418,7 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
         } else if let Some(macro_call) = self.framework.expression_is_macro_call(storage, base) {
             if innermost {
                 let name = macro_call.name();
-                let is_ignored = self.config.is_ignored_macro(&name);
+                let is_ignored = self.is_ignored_macro(storage, &name);
                 return CallInfo {
                     span: macro_call.span(&self.source_file),
                     is_method: false,
@@ -433,6 +459,32 @@ impl<'context, 'config, 'framework, 'ast, T: ParseLow>
         }
     }
 
+    fn is_ignored_function(
+        &self,
+        storage: &RefCell<<T::Types as AbstractTypes>::Storage<'ast>>,
+        name: &str,
+    ) -> bool {
+        self.config.is_ignored_function(name)
+            || self
+                .framework
+                .resolved_names(storage, name)
+                .iter()
+                .any(|name| self.config.is_ignored_function(name))
+    }
+
+    fn is_ignored_macro(
+        &self,
+        storage: &RefCell<<T::Types as AbstractTypes>::Storage<'ast>>,
+        name: &str,
+    ) -> bool {
+        self.config.is_ignored_macro(name)
+            || self
+                .framework
+                .resolved_names(storage, name)
+                .iter()
+                .any(|name| self.config.is_ignored_macro(name))
+    }
+
     fn field_base_is_named_field(
         &self,
         storage: &RefCell<<T::Types as AbstractTypes>::Storage<'ast>>,
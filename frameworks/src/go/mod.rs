@@ -3,7 +3,7 @@ use super::{
     WalkDirResult,
 };
 use anyhow::{anyhow, Context, Result};
-use necessist_core::{util, LightContext, LineColumn, SourceFile, Span};
+use necessist_core::{util, Candidate, LightContext, LineColumn, SourceFile, Span};
 use once_cell::sync::Lazy;
 use std::{
     collections::BTreeMap, convert::Infallible, fs::read_to_string, path::Path, process::Command,
@@ -252,7 +252,7 @@ impl ParseLow for Go {
         generic_visitor: GenericVisitor<'_, '_, '_, 'ast, Self>,
         storage: &std::cell::RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
         file: &'ast <Self::Types as AbstractTypes>::File,
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         visit(generic_visitor, storage, &file.1)
     }
 
@@ -433,7 +433,8 @@ impl RunLow for Go {
         &self,
         context: &LightContext,
         span: &Span,
-    ) -> (Command, Vec<String>, Option<(ProcessLines, String)>) {
+        _build_stdout: &[u8],
+    ) -> Result<(Command, Vec<String>, Option<(ProcessLines, String)>)> {
         #[allow(clippy::expect_used)]
         let test_name = self
             .span_test_name_map
@@ -445,14 +446,14 @@ impl RunLow for Go {
 
         let needle = format!("=== RUN   {test_name}");
 
-        (
+        Ok((
             command,
             Vec::new(),
             Some((
                 (false, Box::new(move |line| line == needle)),
                 test_name.clone(),
             )),
-        )
+        ))
     }
 }
 
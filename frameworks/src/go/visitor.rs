@@ -5,7 +5,7 @@ use super::{
     Statement, Storage, Test, CALL_EXPRESSION_KIND,
 };
 use anyhow::Result;
-use necessist_core::Span;
+use necessist_core::Candidate;
 use once_cell::sync::Lazy;
 use std::{cell::RefCell, iter::Peekable};
 use strum::IntoEnumIterator;
@@ -51,7 +51,7 @@ pub(super) fn visit<'ast>(
     generic_visitor: GenericVisitor<'_, '_, '_, 'ast, Go>,
     storage: &RefCell<Storage<'ast>>,
     tree: &'ast Tree,
-) -> Result<Vec<Span>> {
+) -> Result<Vec<Candidate>> {
     let mut visitor = Visitor::new(generic_visitor, storage);
     visitor.visit_tree(tree)?;
     Ok(visitor.generic_visitor.spans_visited())
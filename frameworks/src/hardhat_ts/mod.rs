@@ -3,7 +3,7 @@ use anyhow::Result;
 use log::debug;
 use necessist_core::{
     framework::{Interface, Postprocess},
-    LightContext, Span,
+    Candidate, LightContext, Span,
 };
 use std::{path::Path, process::Command};
 use subprocess::Exec;
@@ -36,7 +36,7 @@ impl ParseHigh for HardhatTs {
         context: &LightContext,
         config: &necessist_core::config::Toml,
         test_files: &[&Path],
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         self.mocha_adapter.parse(context, config, test_files)
     }
 }
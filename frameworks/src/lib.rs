@@ -90,7 +90,7 @@ impl ToImplementation for Identifier {
             Self::HardhatTs => Ok(Some(Box::new(HardhatTs::new()))),
 
             Self::Rust => Ok(Some(implementation_as_interface(ParseRunAdapter::new)(
-                Rust::new(),
+                Rust::new(context)?,
             ))),
         }
     }
@@ -1,7 +1,9 @@
 use super::{GenericVisitor, ParseHigh};
 use anyhow::{Context, Result};
 use heck::ToKebabCase;
-use necessist_core::{config, util, warn, LightContext, SourceFile, Span, WarnFlags, Warning};
+use necessist_core::{
+    config, util, warn, Candidate, LightContext, SourceFile, Span, WarnFlags, Warning,
+};
 use paste::paste;
 use std::{any::type_name, cell::RefCell, convert::Infallible, path::Path, rc::Rc};
 
@@ -95,7 +97,7 @@ pub trait ParseLow: Sized {
         generic_visitor: GenericVisitor<'_, '_, '_, 'ast, Self>,
         storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
         file: &'ast <Self::Types as AbstractTypes>::File,
-    ) -> Result<Vec<Span>>;
+    ) -> Result<Vec<Candidate>>;
     #[must_use]
     fn on_candidate_found(
         &mut self,
@@ -110,6 +112,24 @@ pub trait ParseLow: Sized {
         storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
         test: <Self::Types as AbstractTypes>::Test<'ast>,
     ) -> Vec<<Self::Types as AbstractTypes>::Statement<'ast>>;
+    // smoelius: A test that should panic passes when it panics, e.g., Rust's `#[should_panic]`.
+    fn test_should_panic<'ast>(
+        &self,
+        _storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
+        _test: <Self::Types as AbstractTypes>::Test<'ast>,
+    ) -> bool {
+        false
+    }
+    // smoelius: A function or macro can be called by a name other than its own, e.g., because of a
+    // Rust `use` item. Ignored function and macro patterns are matched against the names returned
+    // here as well as against the name used in the call.
+    fn resolved_names(
+        &self,
+        _storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'_>>,
+        _name: &str,
+    ) -> Vec<String> {
+        Vec::new()
+    }
 
     fn statement_is_expression<'ast>(
         &self,
@@ -191,13 +211,14 @@ impl<T: ParseLow> ParseLow for Rc<RefCell<T>> {
         generic_visitor: GenericVisitor<'_, '_, '_, 'ast, Self>,
         storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
         file: &'ast <Self::Types as AbstractTypes>::File,
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         let GenericVisitor {
             context,
             config,
             framework,
             source_file,
             test_name,
+            test_should_panic,
             last_statement_in_test,
             n_before,
             n_statement_leaves_visited,
@@ -211,6 +232,7 @@ impl<T: ParseLow> ParseLow for Rc<RefCell<T>> {
             framework: &mut framework,
             source_file,
             test_name,
+            test_should_panic,
             last_statement_in_test,
             n_before,
             n_statement_leaves_visited,
@@ -236,6 +258,20 @@ impl<T: ParseLow> ParseLow for Rc<RefCell<T>> {
     ) -> Vec<<Self::Types as AbstractTypes>::Statement<'ast>> {
         self.borrow().test_statements(storage, test)
     }
+    fn test_should_panic<'ast>(
+        &self,
+        storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
+        test: <Self::Types as AbstractTypes>::Test<'ast>,
+    ) -> bool {
+        self.borrow().test_should_panic(storage, test)
+    }
+    fn resolved_names(
+        &self,
+        storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'_>>,
+        name: &str,
+    ) -> Vec<String> {
+        self.borrow().resolved_names(storage, name)
+    }
     fn statement_is_expression<'ast>(
         &self,
         storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
@@ -323,10 +359,10 @@ impl<T: ParseLow> ParseHigh for ParseAdapter<T> {
         context: &LightContext,
         config: &config::Toml,
         test_files: &[&Path],
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         let config = Self::compile_config(context, config)?;
 
-        let mut spans = Vec::new();
+        let mut candidates = Vec::new();
 
         let walk_dir_results = self.0.walk_dir(context.root);
 
@@ -344,7 +380,7 @@ impl<T: ParseLow> ParseHigh for ParseAdapter<T> {
 
             let storage = RefCell::new(self.0.storage_from_file(&file));
 
-            let source_file = SourceFile::new(context.root.clone(), test_file.to_path_buf())?;
+            let source_file = SourceFile::new(context, test_file.to_path_buf())?;
 
             let generic_visitor = GenericVisitor {
                 context,
@@ -352,6 +388,7 @@ impl<T: ParseLow> ParseHigh for ParseAdapter<T> {
                 framework: &mut self.0,
                 source_file,
                 test_name: None,
+                test_should_panic: false,
                 last_statement_in_test: None,
                 n_statement_leaves_visited: 0,
                 n_before: Vec::new(),
@@ -360,7 +397,7 @@ impl<T: ParseLow> ParseHigh for ParseAdapter<T> {
             };
 
             let spans_visited = T::visit_file(generic_visitor, &storage, &file)?;
-            spans.extend(spans_visited);
+            candidates.extend(spans_visited);
 
             Ok(())
         };
@@ -382,7 +419,7 @@ impl<T: ParseLow> ParseHigh for ParseAdapter<T> {
             }
         }
 
-        Ok(spans)
+        Ok(candidates)
     }
 }
 
@@ -12,11 +12,15 @@ pub trait RunLow {
     const REQUIRES_NODE_MODULES: bool = false;
     fn command_to_run_test_file(&self, context: &LightContext, test_file: &Path) -> Command;
     fn command_to_build_test(&self, context: &LightContext, span: &Span) -> Command;
+    /// Returns the command to run the test containing `span`. `build_stdout` is the standard output
+    /// of the command returned by [`RunLow::command_to_build_test`].
+    #[allow(clippy::type_complexity)]
     fn command_to_run_test(
         &self,
         context: &LightContext,
         span: &Span,
-    ) -> (Command, Vec<String>, Option<(ProcessLines, String)>);
+        build_stdout: &[u8],
+    ) -> Result<(Command, Vec<String>, Option<(ProcessLines, String)>)>;
 }
 
 impl<T: RunLow> RunLow for Rc<RefCell<T>> {
@@ -31,8 +35,10 @@ impl<T: RunLow> RunLow for Rc<RefCell<T>> {
         &self,
         context: &LightContext,
         span: &Span,
-    ) -> (Command, Vec<String>, Option<(ProcessLines, String)>) {
-        self.borrow().command_to_run_test(context, span)
+        build_stdout: &[u8],
+    ) -> Result<(Command, Vec<String>, Option<(ProcessLines, String)>)> {
+        self.borrow()
+            .command_to_run_test(context, span, build_stdout)
     }
 }
 
@@ -65,7 +71,7 @@ impl<T: RunLow> RunHigh for RunAdapter<T> {
         context: &LightContext,
         span: &Span,
     ) -> Result<Option<(Exec, Option<Box<Postprocess>>)>> {
-        {
+        let build_output = {
             let mut command = self.0.command_to_build_test(context, span);
             command.args(&context.opts.args);
 
@@ -76,9 +82,12 @@ impl<T: RunLow> RunHigh for RunAdapter<T> {
                 debug!("{}", output);
                 return Ok(None);
             }
-        }
+            output
+        };
 
-        let (mut command, final_args, init_f_test) = self.0.command_to_run_test(context, span);
+        let (mut command, final_args, init_f_test) =
+            self.0
+                .command_to_run_test(context, span, build_output.stdout())?;
         command.args(&context.opts.args);
         command.args(final_args);
 
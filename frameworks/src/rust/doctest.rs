@@ -0,0 +1,415 @@
+use anyhow::Result;
+use std::ops::RangeInclusive;
+use syn::{
+    AttrStyle, Attribute, Expr, ExprLit, Fields, ImplItem, Item, Lit, Meta, TraitItem, Type,
+};
+
+/// A Rust code block in a `///` or `//!` doc comment
+#[derive(Debug)]
+pub struct Doctest {
+    /// The path of the documented item within the file, e.g., `["Foo", "bar"]`
+    pub path: Vec<String>,
+    /// The lines from the code block's opening fence to its closing fence
+    pub lines: RangeInclusive<usize>,
+}
+
+impl Doctest {
+    /// rustdoc names a doctest after the line of its opening fence.
+    pub fn line(&self) -> usize {
+        *self.lines.start()
+    }
+}
+
+/// A file's doctests, along with a file containing their code. Each line of code appears at the
+/// same line and column as in the original file, so that spans in the latter file are spans in the
+/// original one. As rustdoc does, a doctest's code is wrapped in a `main` function, unless the code
+/// contains one.
+pub struct Doctests {
+    pub file: syn::File,
+    pub doctests: Vec<Doctest>,
+}
+
+const FN_MAIN: &str = "fn main() {";
+
+pub fn extract(content: &str, file: &syn::File) -> Result<Doctests> {
+    let source_lines = content.lines().collect::<Vec<_>>();
+    let mut extractor = Extractor {
+        synthetic_lines: vec![String::new(); source_lines.len()],
+        source_lines,
+        path: Vec::new(),
+        doctests: Vec::new(),
+    };
+
+    extractor.doc_comments(&file.attrs);
+    extractor.items(&file.items);
+
+    let Extractor {
+        synthetic_lines,
+        doctests,
+        ..
+    } = extractor;
+
+    let file = syn::parse_file(&synthetic_lines.join("\n"))?;
+
+    Ok(Doctests { file, doctests })
+}
+
+struct Extractor<'a> {
+    source_lines: Vec<&'a str>,
+    synthetic_lines: Vec<String>,
+    path: Vec<String>,
+    doctests: Vec<Doctest>,
+}
+
+/// A code block whose closing fence has not yet been seen
+struct Block {
+    fence: char,
+    fence_len: usize,
+    runnable: bool,
+    start: usize,
+    /// Each line of code, as its line number, the column at which it starts, and its text
+    code: Vec<(usize, usize, String)>,
+}
+
+impl<'a> Extractor<'a> {
+    fn items(&mut self, items: &[Item]) {
+        for item in items {
+            self.item(item);
+        }
+    }
+
+    // smoelius: Items in function bodies, trait implementations, and macros are not considered.
+    fn item(&mut self, item: &Item) {
+        let (attrs, ident) = match item {
+            Item::Const(item) => (&item.attrs, &item.ident),
+            Item::Enum(item) => {
+                self.with_ident(&item.attrs, &item.ident, |this| {
+                    for variant in &item.variants {
+                        this.with_ident(&variant.attrs, &variant.ident, |_| {});
+                    }
+                });
+                return;
+            }
+            Item::Fn(item) => (&item.attrs, &item.sig.ident),
+            Item::Impl(item) => {
+                if is_cfg_test(&item.attrs) || item.trait_.is_some() {
+                    return;
+                }
+                let Some(ident) = self_ty_ident(&item.self_ty) else {
+                    return;
+                };
+                self.path.push(ident.to_string());
+                for impl_item in &item.items {
+                    match impl_item {
+                        ImplItem::Const(impl_item) => {
+                            self.with_ident(&impl_item.attrs, &impl_item.ident, |_| {});
+                        }
+                        ImplItem::Fn(impl_item) => {
+                            self.with_ident(&impl_item.attrs, &impl_item.sig.ident, |_| {});
+                        }
+                        ImplItem::Type(impl_item) => {
+                            self.with_ident(&impl_item.attrs, &impl_item.ident, |_| {});
+                        }
+                        _ => {}
+                    }
+                }
+                self.path.pop();
+                return;
+            }
+            Item::Mod(item) => {
+                self.with_ident(&item.attrs, &item.ident, |this| {
+                    if let Some((_, items)) = &item.content {
+                        this.items(items);
+                    }
+                });
+                return;
+            }
+            Item::Static(item) => (&item.attrs, &item.ident),
+            Item::Struct(item) => {
+                self.with_ident(&item.attrs, &item.ident, |this| {
+                    if let Fields::Named(fields) = &item.fields {
+                        for field in &fields.named {
+                            if let Some(ident) = &field.ident {
+                                this.with_ident(&field.attrs, ident, |_| {});
+                            }
+                        }
+                    }
+                });
+                return;
+            }
+            Item::Trait(item) => {
+                self.with_ident(&item.attrs, &item.ident, |this| {
+                    for trait_item in &item.items {
+                        match trait_item {
+                            TraitItem::Const(trait_item) => {
+                                this.with_ident(&trait_item.attrs, &trait_item.ident, |_| {});
+                            }
+                            TraitItem::Fn(trait_item) => {
+                                this.with_ident(&trait_item.attrs, &trait_item.sig.ident, |_| {});
+                            }
+                            TraitItem::Type(trait_item) => {
+                                this.with_ident(&trait_item.attrs, &trait_item.ident, |_| {});
+                            }
+                            _ => {}
+                        }
+                    }
+                });
+                return;
+            }
+            Item::Type(item) => (&item.attrs, &item.ident),
+            Item::Union(item) => (&item.attrs, &item.ident),
+            _ => return,
+        };
+        self.with_ident(attrs, ident, |_| {});
+    }
+
+    fn with_ident(&mut self, attrs: &[Attribute], ident: &syn::Ident, f: impl FnOnce(&mut Self)) {
+        // smoelius: rustdoc does not see items that are compiled only for tests.
+        if is_cfg_test(attrs) {
+            return;
+        }
+        self.path.push(ident.to_string());
+        self.doc_comments(attrs);
+        f(self);
+        self.path.pop();
+    }
+
+    fn doc_comments(&mut self, attrs: &[Attribute]) {
+        let mut block: Option<Block> = None;
+        let mut line_prev = None;
+
+        for attr in attrs {
+            let Some((line, column, content)) = self.doc_comment(attr) else {
+                block = None;
+                line_prev = None;
+                continue;
+            };
+
+            // smoelius: A code block must be written on consecutive lines.
+            if line_prev.map_or(false, |line_prev| line_prev + 1 != line) {
+                block = None;
+            }
+            line_prev = Some(line);
+
+            if let Some(block_open) = &mut block {
+                if block_open.is_closed_by(&content) {
+                    if let Some(block) = block.take() {
+                        if block.runnable {
+                            self.push(block, line);
+                        }
+                    }
+                } else {
+                    block_open.code.push((line, column, unhide(&content)));
+                }
+            } else {
+                block = Block::open(line, &content);
+            }
+        }
+    }
+
+    /// Returns the line of a `///` or `//!` doc comment, the column at which its contents start,
+    /// and its contents. Returns `None` for any other attribute, e.g., `#[doc = "..."]`.
+    fn doc_comment(&self, attr: &Attribute) -> Option<(usize, usize, String)> {
+        let Meta::NameValue(name_value) = &attr.meta else {
+            return None;
+        };
+        if !name_value.path.is_ident("doc") {
+            return None;
+        }
+        let Expr::Lit(ExprLit {
+            lit: Lit::Str(lit_str),
+            ..
+        }) = &name_value.value
+        else {
+            return None;
+        };
+
+        let start = <_ as syn::spanned::Spanned>::span(attr).start();
+        let source_line = self.source_lines.get(start.line.checked_sub(1)?)?;
+        let comment = source_line.chars().skip(start.column).collect::<String>();
+        let prefix = match attr.style {
+            AttrStyle::Outer => "///",
+            AttrStyle::Inner(_) => "//!",
+        };
+        let content = comment.strip_prefix(prefix)?.trim_end_matches('\r');
+        if content.starts_with('/') || content != lit_str.value().trim_end_matches('\r') {
+            return None;
+        }
+
+        Some((start.line, start.column + prefix.len(), content.to_owned()))
+    }
+
+    fn push(&mut self, block: Block, end: usize) {
+        let Block { start, code, .. } = block;
+
+        let code_text = code
+            .iter()
+            .map(|(_, _, text)| text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let has_main = syn::parse_file(&code_text).map_or(false, |file| {
+            file.items
+                .iter()
+                .any(|item| matches!(item, Item::Fn(item_fn) if item_fn.sig.ident == "main"))
+        });
+
+        if !has_main && syn::parse_file(&format!("{FN_MAIN}\n{code_text}\n}}")).is_err() {
+            return;
+        }
+
+        if !has_main {
+            self.synthetic_lines[start - 1] = FN_MAIN.to_owned();
+            self.synthetic_lines[end - 1] = "}".to_owned();
+        }
+        for (line, column, text) in code {
+            self.synthetic_lines[line - 1] = format!("{}{text}", " ".repeat(column));
+        }
+
+        self.doctests.push(Doctest {
+            path: self.path.clone(),
+            lines: start..=end,
+        });
+    }
+}
+
+impl Block {
+    fn open(start: usize, content: &str) -> Option<Self> {
+        let trimmed = content.trim_start();
+        let fence = trimmed
+            .chars()
+            .next()
+            .filter(|&ch| ch == '`' || ch == '~')?;
+        let fence_len = trimmed.chars().take_while(|&ch| ch == fence).count();
+        if fence_len < 3 {
+            return None;
+        }
+        let info = &trimmed[fence_len..];
+        if fence == '`' && info.contains('`') {
+            return None;
+        }
+        Some(Self {
+            fence,
+            fence_len,
+            runnable: is_runnable(info),
+            start,
+            code: Vec::new(),
+        })
+    }
+
+    fn is_closed_by(&self, content: &str) -> bool {
+        let trimmed = content.trim_start();
+        let fence_len = trimmed.chars().take_while(|&ch| ch == self.fence).count();
+        fence_len >= self.fence_len && trimmed[fence_len..].trim().is_empty()
+    }
+}
+
+/// Returns true if a code block with info string `info` is a doctest that rustdoc runs. Like
+/// rustdoc, a code block is considered Rust unless its info string names some other language.
+fn is_runnable(info: &str) -> bool {
+    let mut rust = false;
+    let mut other = false;
+    for token in info
+        .split(|ch: char| ch == ',' || ch.is_whitespace())
+        .filter(|token| !token.is_empty())
+    {
+        match token {
+            "rust" => rust = true,
+            "compile_fail" | "no_run" | "test_harness" => return false,
+            "allow_fail" | "should_panic" | "standalone_crate" => {}
+            _ if token == "ignore" || token.starts_with("ignore-") => return false,
+            _ if token.starts_with("edition") => {}
+            _ => other = true,
+        }
+    }
+    rust || !other
+}
+
+/// Reveals a line hidden with `#`, preserving the columns of the line's other characters.
+fn unhide(line: &str) -> String {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    if trimmed == "#" || trimmed.starts_with("# ") || trimmed.starts_with("##") {
+        format!("{} {}", &line[..indent], &trimmed[1..])
+    } else {
+        line.to_owned()
+    }
+}
+
+fn self_ty_ident(ty: &Type) -> Option<&syn::Ident> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    if type_path.qself.is_some() {
+        return None;
+    }
+    let segment = type_path.path.segments.last()?;
+    if !segment.arguments.is_none() {
+        return None;
+    }
+    Some(&segment.ident)
+}
+
+fn is_cfg_test(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        if let Meta::List(list) = &attr.meta {
+            list.path.is_ident("cfg") && list.tokens.to_string() == "test"
+        } else {
+            false
+        }
+    })
+}
+
+#[test]
+fn doctests_are_extracted() {
+    let content = r#"//! ```
+//! setup();
+//! ```
+
+/// ```
+/// let x = 1;
+/// # let y = x;
+/// ```
+///
+/// ```text
+/// not code
+/// ```
+pub fn f() {}
+
+pub struct Foo;
+
+impl Foo {
+    /// ```no_run
+    /// loop {}
+    /// ```
+    ///
+    /// ```should_panic
+    /// fn main() {
+    ///     panic!();
+    /// }
+    /// ```
+    pub fn bar() {}
+}
+"#;
+    let file = syn::parse_file(content).unwrap();
+    let Doctests { file, doctests } = extract(content, &file).unwrap();
+
+    assert_eq!(
+        vec![
+            (Vec::<String>::new(), 1..=3),
+            (vec!["f".to_owned()], 5..=8),
+            (vec!["Foo".to_owned(), "bar".to_owned()], 22..=26),
+        ],
+        doctests
+            .into_iter()
+            .map(|Doctest { path, lines }| (path, lines))
+            .collect::<Vec<_>>()
+    );
+
+    // smoelius: Two wrapped doctests plus one with its own `main`.
+    assert_eq!(3, file.items.len());
+    assert!(matches!(&file.items[1], Item::Fn(item_fn) if {
+        let let_y = <_ as syn::spanned::Spanned>::span(&item_fn.block.stmts[1]).start();
+        (let_y.line, let_y.column) == (7, 6)
+    }));
+}
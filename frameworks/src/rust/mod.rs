@@ -2,32 +2,78 @@ use super::{
     AbstractTypes, GenericVisitor, MaybeNamed, Named, ParseLow, ProcessLines, RunLow, Spanned,
     WalkDirResult,
 };
-use anyhow::Result;
-use cargo_metadata::Package;
-use necessist_core::{warn, LightContext, SourceFile, Span, ToInternalSpan, WarnFlags, Warning};
+use anyhow::{anyhow, Result};
+use cargo_metadata::{
+    camino::{Utf8Path, Utf8PathBuf},
+    Artifact, Message, Package, Target,
+};
+use necessist_core::{
+    config::{self, RustRunner},
+    warn, Candidate, LightContext, SourceFile, Span, ToInternalSpan, WarnFlags, Warning,
+};
 use quote::ToTokens;
 use std::{
     cell::RefCell,
     collections::BTreeMap,
-    ffi::OsStr,
+    env::{consts::EXE_SUFFIX, join_paths, split_paths, var_os},
+    ffi::{OsStr, OsString},
     fs::read_to_string,
     path::{Path, PathBuf},
     process::Command,
 };
 
+mod doctest;
+use doctest::{Doctest, Doctests};
+
+mod module_tree;
+use module_tree::Module;
+
 mod storage;
-use storage::{cached_test_file_package, Storage};
+use storage::{cached_test_file_module, cached_test_file_package, Storage};
+
+mod test_macros;
+use test_macros::MacroTests;
 
 mod try_insert;
 use try_insert::TryInsert;
 
+mod uses;
+use uses::Uses;
+
 mod visitor;
 use visitor::visit;
 
 #[derive(Debug)]
 pub struct Rust {
-    test_file_flags_cache: BTreeMap<PathBuf, Vec<String>>,
-    span_test_path_map: BTreeMap<Span, Vec<String>>,
+    cargo: config::Rust,
+    doctests: bool,
+    include_ignored: bool,
+    runner: RustRunner,
+    test_file_flags_cache: BTreeMap<PathBuf, TestFileFlags>,
+    span_test_path_map: BTreeMap<Span, TestPath>,
+}
+
+#[derive(Debug)]
+struct TestFileFlags {
+    /// `--manifest-path` and the flags from the `[rust]` configuration
+    package: Vec<String>,
+    /// The flags that select the test file's target, e.g., `--test` or `--lib`
+    target_selection: Vec<String>,
+    /// The environment in which to run the test file's test executable directly, or `None` if the
+    /// tests must be run with `cargo test` (see [`test_executable_env`])
+    executable_env: Option<Vec<(String, OsString)>>,
+}
+
+#[derive(Debug)]
+struct TestPath {
+    path: Vec<String>,
+    /// Whether the test expands into a module of tests, one per case (e.g., because it uses
+    /// `#[test_case]`)
+    parameterized: bool,
+    /// Whether the test has an `#[ignore]` attribute, and so must be run with `--include-ignored`
+    ignored: bool,
+    /// If the test is a doctest, its name, e.g., `src/lib.rs - Foo::bar (line 12)`
+    doctest: Option<String>,
 }
 
 impl Rust {
@@ -39,17 +85,22 @@ impl Rust {
             .map_err(Into::into)
     }
 
-    pub fn new() -> Self {
-        Self {
+    pub fn new(context: &LightContext) -> Result<Self> {
+        let config = config::Toml::read(context, context.root)?;
+        Ok(Self {
+            cargo: config.rust.unwrap_or_default(),
+            doctests: config.rust_doctests.unwrap_or_default(),
+            include_ignored: config.rust_include_ignored.unwrap_or_default(),
+            runner: config.rust_runner.unwrap_or_default(),
             test_file_flags_cache: BTreeMap::new(),
             span_test_path_map: BTreeMap::new(),
-        }
+        })
     }
 }
 
 #[derive(Clone, Copy)]
 pub enum Expression<'ast> {
-    Await(&'ast syn::ExprAwait),
+    Await(Await<'ast>),
     Field(Field<'ast>),
     Call(Call<'ast>),
     MacroCall(MacroCall<'ast>),
@@ -59,16 +110,25 @@ pub enum Expression<'ast> {
 impl<'ast> From<&'ast syn::Expr> for Expression<'ast> {
     fn from(value: &'ast syn::Expr) -> Self {
         match value {
-            syn::Expr::Await(await_) => Expression::Await(await_),
+            syn::Expr::Await(await_) => Expression::Await(Await::Await(await_)),
             syn::Expr::Field(field) => Expression::Field(Field::Field(field)),
             syn::Expr::Call(call) => Expression::Call(Call::FunctionCall(call)),
             syn::Expr::Macro(mac) => Expression::MacroCall(MacroCall::Expr(mac)),
             syn::Expr::MethodCall(method_call) => Expression::Call(Call::MethodCall(method_call)),
+            syn::Expr::Try(try_) => Expression::Await(Await::Try(try_)),
             _ => Expression::Other(<_ as syn::spanned::Spanned>::span(value)),
         }
     }
 }
 
+// smoelius: `?` is treated like `.await`: a statement such as `client.connect()?;` is handled like
+// `client.connect();`.
+#[derive(Clone, Copy)]
+pub enum Await<'ast> {
+    Await(&'ast syn::ExprAwait),
+    Try(&'ast syn::ExprTry),
+}
+
 #[derive(Clone, Copy)]
 pub enum Field<'ast> {
     Field(&'ast syn::ExprField),
@@ -98,15 +158,23 @@ impl<'ast> MacroCall<'ast> {
     }
 }
 
+pub struct File {
+    file: syn::File,
+    /// Present only if doctests are enabled. An error is reported as a warning when the file is
+    /// visited, and the file's doctests are skipped.
+    doctests: Option<Result<Doctests>>,
+    macro_tests: MacroTests,
+}
+
 pub struct Types;
 
 impl AbstractTypes for Types {
     type Storage<'ast> = Storage<'ast>;
-    type File = syn::File;
+    type File = File;
     type Test<'ast> = &'ast syn::ItemFn;
     type Statement<'ast> = &'ast syn::Stmt;
     type Expression<'ast> = Expression<'ast>;
-    type Await<'ast> = &'ast syn::ExprAwait;
+    type Await<'ast> = Await<'ast>;
     type Field<'ast> = Field<'ast>;
     type Call<'ast> = Call<'ast>;
     type MacroCall<'ast> = MacroCall<'ast>;
@@ -171,9 +239,12 @@ impl<'ast> Named for <Types as AbstractTypes>::MacroCall<'ast> {
 impl<'ast> Spanned for <Types as AbstractTypes>::Expression<'ast> {
     fn span(&self, source_file: &SourceFile) -> Span {
         match self {
-            Expression::Await(await_) => {
+            Expression::Await(Await::Await(await_)) => {
                 <_ as syn::spanned::Spanned>::span(await_).to_internal_span(source_file)
             }
+            Expression::Await(Await::Try(try_)) => {
+                <_ as syn::spanned::Spanned>::span(try_).to_internal_span(source_file)
+            }
             Expression::Call(call) => call.span(source_file),
             Expression::Field(field) => field.span(source_file),
             Expression::MacroCall(macro_call) => macro_call.span(source_file),
@@ -306,7 +377,18 @@ impl ParseLow for Rust {
 
     fn parse_file(&self, test_file: &Path) -> Result<<Self::Types as AbstractTypes>::File> {
         let content = read_to_string(test_file)?;
-        syn::parse_file(&content).map_err(Into::into)
+        let file = syn::parse_file(&content)?;
+        let doctests = if self.doctests {
+            Some(doctest::extract(&content, &file))
+        } else {
+            None
+        };
+        let macro_tests = test_macros::extract(&file);
+        Ok(File {
+            file,
+            doctests,
+            macro_tests,
+        })
     }
 
     fn storage_from_file<'ast>(
@@ -320,7 +402,7 @@ impl ParseLow for Rust {
         generic_visitor: GenericVisitor<'_, '_, '_, 'ast, Self>,
         storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
         file: &'ast <Self::Types as AbstractTypes>::File,
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         visit(generic_visitor, storage, file)
     }
 
@@ -336,10 +418,31 @@ impl ParseLow for Rust {
         // from being queued.
         #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
         let result = (|| {
-            let _ = self.cached_test_file_flags(
-                &mut storage.borrow_mut().test_file_package_cache,
-                &span.source_file,
-            )?;
+            {
+                let mut storage = storage.borrow_mut();
+                let storage = &mut *storage;
+                let _ = self.cached_test_file_flags(
+                    &mut storage.test_file_module_cache,
+                    &mut storage.test_file_package_cache,
+                    &span.source_file,
+                )?;
+            }
+            let doctest = storage.borrow().doctest;
+            if let Some(doctest) = doctest {
+                let Some((path, name)) = storage.borrow_mut().doctest_path(span, doctest)? else {
+                    return Ok(false);
+                };
+                self.set_span_test_path(
+                    span,
+                    TestPath {
+                        path,
+                        parameterized: false,
+                        ignored: false,
+                        doctest: Some(name),
+                    },
+                );
+                return Ok(true);
+            }
             let test_path = match storage.borrow_mut().test_path(span, test_name) {
                 Ok(test_path) => test_path,
                 Err(error) => {
@@ -352,7 +455,17 @@ impl ParseLow for Rust {
                     return Ok(false);
                 }
             };
-            self.set_span_test_path(span, test_path);
+            let parameterized = storage.borrow().parameterized_test;
+            let ignored = storage.borrow().ignored_test;
+            self.set_span_test_path(
+                span,
+                TestPath {
+                    path: test_path,
+                    parameterized,
+                    ignored,
+                    doctest: None,
+                },
+            );
             Ok(true)
         })();
         match result {
@@ -380,6 +493,22 @@ impl ParseLow for Rust {
             .collect::<Vec<_>>()
     }
 
+    fn test_should_panic<'ast>(
+        &self,
+        _storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
+        test: <Self::Types as AbstractTypes>::Test<'ast>,
+    ) -> bool {
+        visitor::is_should_panic(test)
+    }
+
+    fn resolved_names(
+        &self,
+        storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'_>>,
+        name: &str,
+    ) -> Vec<String> {
+        storage.borrow().resolved_names(name)
+    }
+
     fn statement_is_expression<'ast>(
         &self,
         _storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
@@ -470,7 +599,10 @@ impl ParseLow for Rust {
         _storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
         await_: <Self::Types as AbstractTypes>::Await<'ast>,
     ) -> <Self::Types as AbstractTypes>::Expression<'ast> {
-        Expression::from(&*await_.base)
+        Expression::from(match await_ {
+            Await::Await(await_) => &*await_.base,
+            Await::Try(try_) => &*try_.expr,
+        })
     }
 
     fn field_base<'ast>(
@@ -516,8 +648,21 @@ impl RunLow for Rust {
     }
 
     fn command_to_build_test(&self, context: &LightContext, span: &Span) -> Command {
+        // smoelius: `cargo test --doc --no-run` is an error. Doctests are compiled when they are
+        // run. So for a doctest, just the library is built.
+        if self.is_doctest(span) {
+            let mut command = Command::new("cargo");
+            command.arg("build");
+            command.args(self.package_flags(&span.source_file));
+            command.arg("--lib");
+            return command;
+        }
+
         let mut command = self.test_command(context, &span.source_file);
         command.arg("--no-run");
+        if self.runner == RustRunner::CargoTest {
+            command.arg("--message-format=json");
+        }
         command
     }
 
@@ -525,19 +670,258 @@ impl RunLow for Rust {
         &self,
         context: &LightContext,
         span: &Span,
-    ) -> (Command, Vec<String>, Option<(ProcessLines, String)>) {
-        let test_path = self
+        build_stdout: &[u8],
+    ) -> Result<(Command, Vec<String>, Option<(ProcessLines, String)>)> {
+        let TestPath {
+            path,
+            parameterized,
+            ignored,
+            doctest,
+        } = self
             .span_test_path_map
             .get(span)
             .unwrap_or_else(|| panic!("Test path is not set for {span:?}"));
-        let test = test_path.join("::");
+        let test = path.join("::");
+
+        // smoelius: rustdoc splits its test arguments on whitespace, so a doctest cannot be
+        // selected by its name. Instead, doctests are selected by the documented item's path (or,
+        // for a doctest in a module's inner doc comment, the file's path), and the output is
+        // checked for the doctest's name. Doctests are always run with `cargo test`, because
+        // nextest does not support them.
+        if let Some(name) = doctest {
+            let mut command = Command::new("cargo");
+            command.arg("test");
+            command.args(self.package_flags(&span.source_file));
+            command.arg("--doc");
+            let filter = if path.is_empty() {
+                name.split(" - ").next().unwrap_or(name).to_owned()
+            } else {
+                test
+            };
+            let prefix = format!("test {name} ... ");
+            return Ok((
+                command,
+                vec!["--".to_owned(), filter],
+                Some((
+                    (false, Box::new(move |line: &str| line.starts_with(&prefix))),
+                    name.clone(),
+                )),
+            ));
+        }
+
+        // smoelius: A parameterized test is run by running every test in the module it expands
+        // into. Each case must be run, since the removal could affect any of them.
+        if self.runner == RustRunner::Nextest {
+            let mut command = self.test_command(context, &span.source_file);
+            // smoelius: nextest's libtest-compatible JSON output is experimental, and must be
+            // enabled explicitly.
+            command.env("NEXTEST_EXPERIMENTAL_LIBTEST_JSON", "1");
+            let filter = if *parameterized {
+                format!("test(/^{test}::/)")
+            } else {
+                format!("test(={test})")
+            };
+            let mut args = vec!["--message-format=libtest-json".to_owned()];
+            if *ignored {
+                args.extend(["--run-ignored".to_owned(), "all".to_owned()]);
+            }
+            args.extend(["-E".to_owned(), filter]);
+            return Ok((
+                command,
+                args,
+                Some(((false, Box::new(nextest_test_started)), test)),
+            ));
+        }
+
+        let mut filter_args = if *parameterized {
+            vec![format!("{test}::")]
+        } else {
+            vec!["--exact".to_owned(), test.clone()]
+        };
+        if *ignored {
+            filter_args.push("--include-ignored".to_owned());
+        }
+
+        let ran: Box<dyn Fn(&str) -> bool> = if *parameterized {
+            Box::new(|line| line.starts_with("running ") && line != "running 0 tests")
+        } else {
+            Box::new(|line| line == "running 1 test")
+        };
+
+        let init_f_test: Option<(ProcessLines, String)> = Some(((false, ran), test));
+
+        // smoelius: Additional arguments are passed to `cargo test`, and might not be understood by
+        // the test executable. So if there are any, the test is run with `cargo test`.
+        if let (true, Some(env)) = (
+            context.opts.args.is_empty(),
+            self.executable_env(&span.source_file),
+        ) {
+            if let Some((executable, manifest_path)) =
+                test_executable(&span.source_file, build_stdout)?
+            {
+                let package_dir = manifest_path
+                    .parent()
+                    .ok_or_else(|| anyhow!("Failed to get parent of {}", manifest_path))?;
+                // smoelius: `cargo test` runs a test executable from its package's directory.
+                let mut command = Command::new(&executable);
+                command.current_dir(package_dir);
+                command.envs(env.iter().map(|(key, value)| (key, value)));
+                command.env(DYLIB_PATH_VAR, dylib_path(&executable)?);
+                return Ok((command, filter_args, init_f_test));
+            }
+        }
 
-        (
+        Ok((
             self.test_command(context, &span.source_file),
-            vec!["--".to_owned(), "--exact".to_owned(), test.clone()],
-            Some(((false, Box::new(|line| line == "running 1 test")), test)),
-        )
+            std::iter::once("--".to_owned())
+                .chain(filter_args)
+                .collect(),
+            init_f_test,
+        ))
+    }
+}
+
+/// Returns true if `line` is a libtest JSON event reporting that a test started
+fn nextest_test_started(line: &str) -> bool {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+        return false;
+    };
+    value["type"] == "test" && value["event"] == "started"
+}
+
+/// Returns the test executable built for `test_file` and its package's manifest path, according to
+/// the messages that `cargo test --no-run --message-format=json` wrote to `build_stdout`. Returns
+/// `None` if the executable cannot be determined, e.g., because `test_file` could belong to either
+/// a library or a binary.
+fn test_executable(
+    test_file: &Path,
+    build_stdout: &[u8],
+) -> Result<Option<(Utf8PathBuf, Utf8PathBuf)>> {
+    let mut executables = Vec::new();
+
+    for message in Message::parse_stream(build_stdout) {
+        let Message::CompilerArtifact(Artifact {
+            manifest_path,
+            target,
+            profile,
+            executable: Some(executable),
+            ..
+        }) = message?
+        else {
+            continue;
+        };
+        if profile.test {
+            executables.push((target.src_path, executable, manifest_path));
+        }
+    }
+
+    if let Some(index) = executables
+        .iter()
+        .position(|(src_path, _, _)| src_path == test_file)
+    {
+        let (_, executable, manifest_path) = executables.swap_remove(index);
+        return Ok(Some((executable, manifest_path)));
     }
+
+    if let [(_, executable, manifest_path)] = executables.as_slice() {
+        return Ok(Some((executable.clone(), manifest_path.clone())));
+    }
+
+    Ok(None)
+}
+
+// smoelius: The variable through which `cargo test` tells the platform's dynamic loader where to
+// find libraries.
+const DYLIB_PATH_VAR: &str = if cfg!(windows) {
+    "PATH"
+} else if cfg!(target_os = "macos") {
+    "DYLD_FALLBACK_LIBRARY_PATH"
+} else {
+    "LD_LIBRARY_PATH"
+};
+
+/// Returns the environment variables that `cargo test` sets for `package`'s test executables, other
+/// than the dynamic library search path, which depends on where the executable is. Returns `None`
+/// if the environment cannot be reproduced: if `cargo` cannot be found, or if the package has a
+/// build script, which could set `OUT_DIR` or add to the search path.
+fn test_executable_env(package: &Package) -> Option<Vec<(String, OsString)>> {
+    if package
+        .targets
+        .iter()
+        .any(|target| target.kind.iter().any(|kind| kind == "custom-build"))
+    {
+        return None;
+    }
+
+    let cargo = var_os("CARGO").or_else(|| {
+        var_os("PATH").and_then(|path| {
+            split_paths(&path)
+                .map(|dir| dir.join(format!("cargo{EXE_SUFFIX}")))
+                .find(|path| path.is_file())
+                .map(PathBuf::into_os_string)
+        })
+    })?;
+
+    let manifest_dir = package.manifest_path.parent()?;
+    let version = &package.version;
+
+    Some(
+        [
+            ("CARGO", cargo),
+            ("CARGO_MANIFEST_DIR", manifest_dir.as_os_str().to_owned()),
+            ("CARGO_PKG_AUTHORS", package.authors.join(":").into()),
+            (
+                "CARGO_PKG_DESCRIPTION",
+                or_empty(package.description.as_ref()),
+            ),
+            ("CARGO_PKG_HOMEPAGE", or_empty(package.homepage.as_ref())),
+            ("CARGO_PKG_LICENSE", or_empty(package.license.as_ref())),
+            (
+                "CARGO_PKG_LICENSE_FILE",
+                or_empty(package.license_file.as_ref()),
+            ),
+            ("CARGO_PKG_NAME", package.name.clone().into()),
+            ("CARGO_PKG_README", or_empty(package.readme.as_ref())),
+            (
+                "CARGO_PKG_REPOSITORY",
+                or_empty(package.repository.as_ref()),
+            ),
+            (
+                "CARGO_PKG_RUST_VERSION",
+                or_empty(package.rust_version.as_ref()),
+            ),
+            ("CARGO_PKG_VERSION", version.to_string().into()),
+            ("CARGO_PKG_VERSION_MAJOR", version.major.to_string().into()),
+            ("CARGO_PKG_VERSION_MINOR", version.minor.to_string().into()),
+            ("CARGO_PKG_VERSION_PATCH", version.patch.to_string().into()),
+            ("CARGO_PKG_VERSION_PRE", version.pre.to_string().into()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key.to_owned(), value))
+        .collect(),
+    )
+}
+
+fn or_empty<T: ToString>(value: Option<&T>) -> OsString {
+    value.map(ToString::to_string).unwrap_or_default().into()
+}
+
+/// Returns the dynamic library search path with which `cargo test` runs `executable`: the
+/// executable's directory (i.e., `target/<profile>/deps`) and its parent, followed by the current
+/// search path
+fn dylib_path(executable: &Utf8Path) -> Result<OsString> {
+    let deps = executable
+        .parent()
+        .ok_or_else(|| anyhow!("Failed to get parent of {}", executable))?;
+    let paths = [deps, deps.parent().unwrap_or(deps)]
+        .into_iter()
+        .map(|dir| dir.as_std_path().to_path_buf())
+        .chain(
+            var_os(DYLIB_PATH_VAR)
+                .map(|value| split_paths(&value).collect::<Vec<_>>())
+                .unwrap_or_default(),
+        );
+    join_paths(paths).map_err(Into::into)
 }
 
 impl Rust {
@@ -548,28 +932,48 @@ impl Rust {
             .get(test_file)
             .expect("Flags are not cached");
         let mut command = Command::new("cargo");
-        command.arg("test");
-        command.args(flags);
+        match self.runner {
+            RustRunner::CargoTest => {
+                command.arg("test");
+            }
+            RustRunner::Nextest => {
+                command.args(["nextest", "run"]);
+            }
+        }
+        command.args(&flags.package);
+        command.args(&flags.target_selection);
         command
     }
 
     #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
     fn cached_test_file_flags(
         &mut self,
+        test_file_module_map: &mut BTreeMap<PathBuf, Option<Module>>,
         test_file_package_map: &mut BTreeMap<PathBuf, Package>,
         test_file: &Path,
-    ) -> Result<&Vec<String>> {
+    ) -> Result<&TestFileFlags> {
         self.test_file_flags_cache
             .entry(test_file.to_path_buf())
             .or_try_insert_with(|| {
+                let module = cached_test_file_module(
+                    test_file_module_map,
+                    test_file_package_map,
+                    test_file,
+                )?
+                .cloned();
                 let package = cached_test_file_package(test_file_package_map, test_file)?;
 
-                let mut flags = vec![
+                let mut package_flags = vec![
                     "--manifest-path".to_owned(),
                     package.manifest_path.as_str().to_owned(),
                 ];
+                package_flags.extend(self.cargo.cargo_flags(&package.name));
 
-                if let Some(name) = test_file_test(package, test_file) {
+                let mut flags = Vec::new();
+
+                if let Some(module) = module {
+                    flags.extend(target_selection_flags(&module.target));
+                } else if let Some(name) = test_file_test(package, test_file) {
                     flags.extend(["--test".to_owned(), name.clone()]);
                 } else {
                     // smoelius: Failed to find a test target with this file name. Assume it is a
@@ -591,16 +995,57 @@ impl Rust {
                     }
                 }
 
-                Ok(flags)
+                Ok(TestFileFlags {
+                    package: package_flags,
+                    target_selection: flags,
+                    executable_env: test_executable_env(package),
+                })
             })
             .map(|value| value as &_)
     }
 
-    fn set_span_test_path(&mut self, span: &Span, test_path: Vec<String>) {
+    /// Returns the flags cached for `test_file`, less those that select its target
+    fn package_flags(&self, test_file: &Path) -> &[String] {
+        #[allow(clippy::expect_used)]
+        let flags = self
+            .test_file_flags_cache
+            .get(test_file)
+            .expect("Flags are not cached");
+        &flags.package
+    }
+
+    fn executable_env(&self, test_file: &Path) -> Option<&[(String, OsString)]> {
+        #[allow(clippy::expect_used)]
+        let flags = self
+            .test_file_flags_cache
+            .get(test_file)
+            .expect("Flags are not cached");
+        flags.executable_env.as_deref()
+    }
+
+    fn is_doctest(&self, span: &Span) -> bool {
+        self.span_test_path_map
+            .get(span)
+            .map_or(false, |test_path| test_path.doctest.is_some())
+    }
+
+    fn set_span_test_path(&mut self, span: &Span, test_path: TestPath) {
         self.span_test_path_map.insert(span.clone(), test_path);
     }
 }
 
+/// Returns the flags that select `target`, e.g., `--example <name>` for an example
+fn target_selection_flags(target: &Target) -> Vec<String> {
+    if module_tree::is_lib(target) {
+        return vec!["--lib".to_owned()];
+    }
+    ["bench", "bin", "example", "test"]
+        .into_iter()
+        .find(|kind| target.kind.iter().any(|other| other == kind))
+        .map(|kind| vec![format!("--{kind}"), target.name.clone()])
+        .unwrap_or_default()
+}
+
 fn test_file_test<'a>(package: &'a Package, test_file: &Path) -> Option<&'a String> {
     if let &[name] = package
         .targets
@@ -620,3 +1065,22 @@ fn test_file_test<'a>(package: &'a Package, test_file: &Path) -> Option<&'a Stri
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::nextest_test_started;
+
+    #[test]
+    fn nextest_test_started_matches_only_test_started_events() {
+        assert!(nextest_test_started(
+            r#"{"type":"test","event":"started","name":"basic::basic$passed"}"#
+        ));
+        assert!(!nextest_test_started(
+            r#"{"type":"suite","event":"started","test_count":1}"#
+        ));
+        assert!(!nextest_test_started(
+            r#"{"type":"test","event":"ok","name":"basic::basic$passed"}"#
+        ));
+        assert!(!nextest_test_started("running 1 test"));
+    }
+}
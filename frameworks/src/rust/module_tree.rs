@@ -0,0 +1,154 @@
+use cargo_metadata::{Package, Target};
+use std::{
+    collections::BTreeSet,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+use syn::{Attribute, Expr, ExprLit, Item, Lit, Meta};
+
+/// A file's place in its package: the target whose module tree includes the file, and the file's
+/// module path within that target
+#[derive(Clone, Debug)]
+pub struct Module {
+    pub target: Target,
+    pub path: Vec<String>,
+}
+
+/// Returns the first of `package`'s targets whose module tree includes `test_file`, along with the
+/// file's module path. The module trees are found by following `mod` items from each target's
+/// root, so files in `examples/` and `benches/`, and files named by `#[path = "..."]`
+/// attributes, are found as well.
+pub fn find(package: &Package, test_file: &Path) -> Option<Module> {
+    package
+        .targets
+        .iter()
+        .filter(|target| target.kind.iter().all(|kind| kind != "custom-build"))
+        .find_map(|target| {
+            let mut walker = Walker {
+                test_file,
+                visited: BTreeSet::new(),
+            };
+            walker
+                .walk_file(target.src_path.as_std_path(), true, &mut Vec::new())
+                .map(|path| Module {
+                    target: target.clone(),
+                    path,
+                })
+        })
+}
+
+/// Returns true if `target` is a library, i.e., if it is selected by `cargo test --lib`
+pub fn is_lib(target: &Target) -> bool {
+    target.kind.iter().any(|kind| {
+        ["cdylib", "dylib", "lib", "proc-macro", "rlib", "staticlib"].contains(&kind.as_str())
+    })
+}
+
+struct Walker<'a> {
+    test_file: &'a Path,
+    visited: BTreeSet<PathBuf>,
+}
+
+impl<'a> Walker<'a> {
+    // smoelius: A "mod-rs" file is a crate root, a `mod.rs` file, or a file named by a `#[path]`
+    // attribute. The modules that such a file declares are found relative to the file's directory.
+    // The modules that other files declare are found relative to a directory named after the file,
+    // e.g., `foo.rs`'s modules are found in `foo/`.
+    fn walk_file(
+        &mut self,
+        file: &Path,
+        mod_rs: bool,
+        module_path: &mut Vec<String>,
+    ) -> Option<Vec<String>> {
+        if file == self.test_file {
+            return Some(module_path.clone());
+        }
+
+        // smoelius: Guard against `#[path]` attributes that form a cycle.
+        if !self.visited.insert(file.to_path_buf()) {
+            return None;
+        }
+
+        let contents = read_to_string(file).ok()?;
+        let syntax = syn::parse_file(&contents).ok()?;
+
+        let file_dir = file.parent()?;
+        let module_dir = if mod_rs {
+            file_dir.to_path_buf()
+        } else {
+            file_dir.join(file.file_stem()?)
+        };
+
+        self.walk_items(&syntax.items, file_dir, &module_dir, false, module_path)
+    }
+
+    fn walk_items(
+        &mut self,
+        items: &[Item],
+        file_dir: &Path,
+        module_dir: &Path,
+        inline: bool,
+        module_path: &mut Vec<String>,
+    ) -> Option<Vec<String>> {
+        for item in items {
+            let Item::Mod(item_mod) = item else {
+                continue;
+            };
+
+            let name = item_mod.ident.to_string();
+            let path_attr = path_attr(&item_mod.attrs);
+
+            module_path.push(name.clone());
+
+            let found = if let Some((_, items)) = &item_mod.content {
+                let module_dir = module_dir.join(path_attr.as_deref().unwrap_or(&name));
+                self.walk_items(items, file_dir, &module_dir, true, module_path)
+            } else if let Some(path) = path_attr {
+                // smoelius: A `#[path]` attribute outside of an inline module is relative to the
+                // directory of the file in which it appears. The path is canonicalized because it
+                // could contain `..`.
+                let file = if inline {
+                    module_dir.join(path)
+                } else {
+                    file_dir.join(path)
+                };
+                let file = file.canonicalize().unwrap_or(file);
+                self.walk_file(&file, true, module_path)
+            } else {
+                let file = module_dir.join(format!("{name}.rs"));
+                if file.try_exists().unwrap_or_default() {
+                    self.walk_file(&file, false, module_path)
+                } else {
+                    self.walk_file(&module_dir.join(&name).join("mod.rs"), true, module_path)
+                }
+            };
+
+            module_path.pop();
+
+            if found.is_some() {
+                return found;
+            }
+        }
+
+        None
+    }
+}
+
+fn path_attr(attrs: &[Attribute]) -> Option<String> {
+    attrs.iter().find_map(|attr| {
+        if !attr.path().is_ident("path") {
+            return None;
+        }
+        let Meta::NameValue(name_value) = &attr.meta else {
+            return None;
+        };
+        let Expr::Lit(ExprLit {
+            lit: Lit::Str(lit_str),
+            ..
+        }) = &name_value.value
+        else {
+            return None;
+        };
+        Some(lit_str.value())
+    })
+}
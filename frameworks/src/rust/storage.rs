@@ -1,4 +1,4 @@
-use super::TryInsert;
+use super::{module_tree, Doctest, File, MacroTests, Module, TryInsert, Uses};
 use anyhow::{anyhow, Error, Result};
 use cargo_metadata::{MetadataCommand, Package};
 use necessist_core::{util, Span};
@@ -7,20 +7,41 @@ use std::{
     ffi::OsStr,
     path::{Path, PathBuf},
 };
-use syn::{File, Ident};
+use syn::Ident;
 
 /// Structures needed during parsing but not after.
 pub struct Storage<'ast> {
     pub module_path: Vec<&'ast Ident>,
+    /// The `use` items of the file and of each enclosing inline module, innermost last
+    pub uses: Vec<Uses>,
+    /// Whether the test being visited expands into a module of tests, e.g., because it uses
+    /// `#[test_case]`
+    pub parameterized_test: bool,
+    /// Whether the test being visited has an `#[ignore]` attribute
+    pub ignored_test: bool,
+    /// The tests in the file's test-generating macros, e.g., `proptest!`
+    pub macro_tests: &'ast MacroTests,
+    /// The file's doctests, while they are being visited
+    pub doctests: Option<&'ast [Doctest]>,
+    /// The doctest being visited, if any
+    pub doctest: Option<&'ast Doctest>,
+    pub test_file_module_cache: BTreeMap<PathBuf, Option<Module>>,
     pub test_file_fs_module_path_cache: BTreeMap<PathBuf, Vec<String>>,
     pub test_file_package_cache: BTreeMap<PathBuf, Package>,
     pub error: Option<Error>,
 }
 
 impl<'ast> Storage<'ast> {
-    pub fn new(_file: &'ast File) -> Self {
+    pub fn new(file: &'ast File) -> Self {
         Self {
+            macro_tests: &file.macro_tests,
             module_path: Vec::new(),
+            uses: Vec::new(),
+            parameterized_test: false,
+            ignored_test: false,
+            doctests: None,
+            doctest: None,
+            test_file_module_cache: BTreeMap::new(),
             test_file_fs_module_path_cache: BTreeMap::new(),
             test_file_package_cache: BTreeMap::new(),
             error: None,
@@ -28,16 +49,104 @@ impl<'ast> Storage<'ast> {
     }
 
     pub fn test_path(&mut self, span: &Span, name: &str) -> Result<Vec<String>> {
-        let mut test_path = cached_test_file_fs_module_path(
-            &mut self.test_file_fs_module_path_cache,
+        let module = cached_test_file_module(
+            &mut self.test_file_module_cache,
             &mut self.test_file_package_cache,
             &span.source_file,
-        )
-        .cloned()?;
+        )?;
+        // smoelius: If the test file is not part of any target's module tree, fall back to deriving
+        // the module path from the file's location.
+        let mut test_path = if let Some(module) = module {
+            module.path.clone()
+        } else {
+            cached_test_file_fs_module_path(
+                &mut self.test_file_fs_module_path_cache,
+                &mut self.test_file_package_cache,
+                &span.source_file,
+            )
+            .cloned()?
+        };
         test_path.extend(self.module_path.iter().map(ToString::to_string));
         test_path.push(name.to_string());
         Ok(test_path)
     }
+
+    /// Returns the paths to which the function or macro path `name` could refer because of `use`
+    /// items in scope, excluding `name` itself
+    pub fn resolved_names(&self, name: &str) -> Vec<String> {
+        let mut resolved_names = Vec::new();
+        for uses in self.uses.iter().rev() {
+            for resolved_name in uses.resolve(name) {
+                if resolved_name != name && !resolved_names.contains(&resolved_name) {
+                    resolved_names.push(resolved_name);
+                }
+            }
+        }
+        resolved_names
+    }
+
+    /// Returns the path of the item that `doctest` documents, and the doctest's name. Returns
+    /// `None` if the test file is not part of a library, since only a library's doctests are run.
+    pub fn doctest_path(
+        &mut self,
+        span: &Span,
+        doctest: &Doctest,
+    ) -> Result<Option<(Vec<String>, String)>> {
+        let package =
+            cached_test_file_package(&mut self.test_file_package_cache, &span.source_file)?;
+
+        let manifest_dir = package
+            .manifest_path
+            .parent()
+            .ok_or_else(|| anyhow!("Failed to get parent directory"))?;
+
+        let relative_path = util::strip_prefix(&span.source_file, manifest_dir.as_std_path())?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let module = cached_test_file_module(
+            &mut self.test_file_module_cache,
+            &mut self.test_file_package_cache,
+            &span.source_file,
+        )?;
+        let Some(module) = module.filter(|module| module_tree::is_lib(&module.target)) else {
+            return Ok(None);
+        };
+
+        let mut path = module.path.clone();
+        path.extend(doctest.path.iter().cloned());
+
+        let name = if path.is_empty() {
+            format!("{relative_path} - (line {})", doctest.line())
+        } else {
+            format!(
+                "{relative_path} - {} (line {})",
+                path.join("::"),
+                doctest.line()
+            )
+        };
+
+        Ok(Some((path, name)))
+    }
+}
+
+/// Returns `test_file`'s place in its package's module trees, or `None` if the file is not part of
+/// any of them
+#[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
+pub(super) fn cached_test_file_module<'a>(
+    test_file_module_map: &'a mut BTreeMap<PathBuf, Option<Module>>,
+    test_file_package_map: &mut BTreeMap<PathBuf, Package>,
+    test_file: &Path,
+) -> Result<Option<&'a Module>> {
+    test_file_module_map
+        .entry(test_file.to_path_buf())
+        .or_try_insert_with(|| {
+            let package = cached_test_file_package(test_file_package_map, test_file)?;
+            Ok::<_, Error>(module_tree::find(package, test_file))
+        })
+        .map(|value| value.as_ref())
 }
 
 #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
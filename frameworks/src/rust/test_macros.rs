@@ -0,0 +1,141 @@
+use proc_macro2::TokenStream;
+use std::collections::BTreeMap;
+use syn::{
+    parenthesized,
+    parse::{ParseStream, Parser},
+    parse_quote,
+    visit::{visit_item_macro, Visit},
+    Attribute, Block, Ident, ItemFn, ItemMacro, ReturnType, Token,
+};
+
+// smoelius: Macros that expand into tests, one per function in the macro's body. A test's name is
+// that of its function.
+const PROPTEST_MACROS: &[&str] = &["proptest", "proptest::proptest"];
+const QUICKCHECK_MACROS: &[&str] = &["quickcheck", "quickcheck::quickcheck"];
+
+/// The tests in a file's test-generating macros (e.g., `proptest!`), keyed by the line and column
+/// at which each macro begins. The tests' spans are those of the original source.
+pub type MacroTests = BTreeMap<(usize, usize), Vec<ItemFn>>;
+
+pub fn extract(file: &syn::File) -> MacroTests {
+    let mut extractor = Extractor::default();
+    extractor.visit_file(file);
+    extractor.macro_tests
+}
+
+/// Returns the key under which `item`'s tests are stored in a [`MacroTests`]
+pub fn key(item: &ItemMacro) -> (usize, usize) {
+    let start = <_ as syn::spanned::Spanned>::span(item).start();
+    (start.line, start.column)
+}
+
+#[derive(Default)]
+struct Extractor {
+    macro_tests: MacroTests,
+}
+
+impl<'ast> Visit<'ast> for Extractor {
+    fn visit_item_macro(&mut self, item: &'ast ItemMacro) {
+        let path = item
+            .mac
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::");
+
+        // smoelius: A macro body that cannot be parsed is not treated as containing tests.
+        let tests = if PROPTEST_MACROS.contains(&path.as_str()) {
+            parse_proptest.parse2(item.mac.tokens.clone()).ok()
+        } else if QUICKCHECK_MACROS.contains(&path.as_str()) {
+            parse_quickcheck.parse2(item.mac.tokens.clone()).ok()
+        } else {
+            None
+        };
+
+        if let Some(tests) = tests {
+            self.macro_tests.insert(key(item), tests);
+        }
+
+        visit_item_macro(self, item);
+    }
+}
+
+// smoelius: A `proptest!` function's parameters have the form `pattern in strategy`, which is not
+// valid Rust. Since the parameters are not needed, they are dropped. The function's own `#[test]`
+// attribute is kept.
+fn parse_proptest(input: ParseStream) -> syn::Result<Vec<ItemFn>> {
+    // smoelius: Skip inner attributes, e.g., `#![proptest_config(...)]`.
+    let _ = input.call(Attribute::parse_inner)?;
+
+    let mut tests = Vec::new();
+    while !input.is_empty() {
+        let attrs = input.call(Attribute::parse_outer)?;
+        input.parse::<Token![fn]>()?;
+        let ident: Ident = input.parse()?;
+        let parameters;
+        parenthesized!(parameters in input);
+        parameters.parse::<TokenStream>()?;
+        let output: ReturnType = input.parse()?;
+        let block: Block = input.parse()?;
+        tests.push(parse_quote! {
+            #(#attrs)*
+            fn #ident() #output #block
+        });
+    }
+    Ok(tests)
+}
+
+// smoelius: A `quickcheck!` function is valid Rust, but lacks a `#[test]` attribute, which the
+// macro adds.
+fn parse_quickcheck(input: ParseStream) -> syn::Result<Vec<ItemFn>> {
+    let mut tests = Vec::new();
+    while !input.is_empty() {
+        let mut item: ItemFn = input.parse()?;
+        item.attrs.push(parse_quote! { #[test] });
+        tests.push(item);
+    }
+    Ok(tests)
+}
+
+#[cfg(test)]
+mod test {
+    use super::extract;
+
+    #[test]
+    fn macro_tests_are_extracted() {
+        let file = syn::parse_file(
+            r"
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+    #[test]
+    fn addition(a in 0..10u32, ref b in any::<u32>()) {
+        prop_assert!(a + 0 == a);
+    }
+}
+
+quickcheck::quickcheck! {
+    fn reverse(xs: Vec<u32>) -> bool {
+        xs == xs
+    }
+}
+
+not_a_test_macro! {
+    fn f() {}
+}
+",
+        )
+        .unwrap();
+
+        let names = extract(&file)
+            .into_values()
+            .flatten()
+            .map(|item| {
+                assert!(item.attrs.iter().any(|attr| attr.path().is_ident("test")));
+                item.sig.ident.to_string()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(["addition", "reverse"].as_slice(), names);
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+use syn::{Item, UseTree};
+
+/// The names that a module's `use` items bring into scope
+#[derive(Debug, Default)]
+pub struct Uses {
+    /// Maps each name to the path to which it refers, e.g., `check` to
+    /// `pretty_assertions::assert_eq` for `use pretty_assertions::assert_eq as check;`
+    names: BTreeMap<String, Vec<String>>,
+    /// The paths of glob imports, e.g., `my_test_utils` for `use my_test_utils::*;`
+    globs: Vec<Vec<String>>,
+}
+
+impl Uses {
+    pub fn new(items: &[Item]) -> Self {
+        let mut uses = Self::default();
+        for item in items {
+            if let Item::Use(item_use) = item {
+                uses.insert(&mut Vec::new(), &item_use.tree);
+            }
+        }
+        uses
+    }
+
+    fn insert(&mut self, prefix: &mut Vec<String>, tree: &UseTree) {
+        match tree {
+            UseTree::Path(use_path) => {
+                prefix.push(use_path.ident.to_string());
+                self.insert(prefix, &use_path.tree);
+                prefix.pop();
+            }
+            UseTree::Name(use_name) => {
+                // smoelius: `self` in `use foo::{self, ...}` brings `foo` into scope.
+                if use_name.ident == "self" {
+                    if let Some(name) = prefix.last() {
+                        self.names.insert(name.clone(), prefix.clone());
+                    }
+                } else {
+                    let name = use_name.ident.to_string();
+                    let mut path = prefix.clone();
+                    path.push(name.clone());
+                    self.names.insert(name, path);
+                }
+            }
+            UseTree::Rename(use_rename) => {
+                if use_rename.rename == "_" {
+                    return;
+                }
+                let mut path = prefix.clone();
+                if use_rename.ident != "self" {
+                    path.push(use_rename.ident.to_string());
+                }
+                self.names.insert(use_rename.rename.to_string(), path);
+            }
+            UseTree::Glob(_) => {
+                self.globs.push(prefix.clone());
+            }
+            UseTree::Group(use_group) => {
+                for tree in &use_group.items {
+                    self.insert(prefix, tree);
+                }
+            }
+        }
+    }
+
+    /// Returns the paths to which `path` (e.g., a called function's path) could refer because of
+    /// these `use` items. A name brought into scope by name shadows those brought into scope by
+    /// globs, and a name brought into scope by a glob could come from any of them.
+    pub fn resolve(&self, path: &str) -> Vec<String> {
+        // smoelius: A path with a leading `::` is not affected by `use` items.
+        if path.starts_with("::") {
+            return Vec::new();
+        }
+
+        let segments = path.split("::").collect::<Vec<_>>();
+        let Some((&first, rest)) = segments.split_first() else {
+            return Vec::new();
+        };
+
+        if let Some(prefix) = self.names.get(first) {
+            return vec![join(prefix, rest)];
+        }
+
+        self.globs
+            .iter()
+            .map(|prefix| join(prefix, &segments))
+            .collect()
+    }
+}
+
+fn join(prefix: &[String], segments: &[&str]) -> String {
+    prefix
+        .iter()
+        .map(String::as_str)
+        .chain(segments.iter().copied())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+#[cfg(test)]
+mod test {
+    use super::Uses;
+    use syn::{parse_quote, File};
+
+    #[test]
+    fn uses_are_resolved() {
+        let file: File = parse_quote! {
+            use pretty_assertions::assert_eq as check;
+            use my_test_utils::*;
+            use std::{fs::{self, read_to_string}, process::Command as _};
+        };
+        let uses = Uses::new(&file.items);
+
+        assert_eq!(
+            ["pretty_assertions::assert_eq"].as_slice(),
+            uses.resolve("check")
+        );
+        assert_eq!(["std::fs::write"].as_slice(), uses.resolve("fs::write"));
+        assert_eq!(
+            ["std::fs::read_to_string"].as_slice(),
+            uses.resolve("read_to_string")
+        );
+        assert_eq!(["my_test_utils::setup"].as_slice(), uses.resolve("setup"));
+        assert_eq!(
+            ["my_test_utils::Command::new"].as_slice(),
+            uses.resolve("Command::new")
+        );
+    }
+}
@@ -1,24 +1,80 @@
-use super::{Call, GenericVisitor, MacroCall, Rust, Storage};
+use super::{test_macros, Call, Doctest, File, GenericVisitor, MacroCall, Rust, Storage, Uses};
 use anyhow::Result;
-use necessist_core::Span;
+use necessist_core::{config, source_warn, Candidate, WarnFlags, Warning};
 use std::cell::RefCell;
 use syn::{
     visit::{
-        visit_expr_call, visit_expr_macro, visit_expr_method_call, visit_item_fn, visit_item_mod,
-        visit_stmt, visit_stmt_macro, Visit,
+        visit_expr_call, visit_expr_macro, visit_expr_method_call, visit_file, visit_item_fn,
+        visit_item_mod, visit_stmt, visit_stmt_macro, Visit,
     },
-    ExprCall, ExprMacro, ExprMethodCall, File, Ident, ItemFn, ItemMod, PathSegment, Stmt,
-    StmtMacro,
+    Attribute, Expr, ExprAwait, ExprCall, ExprMacro, ExprMethodCall, ExprParen, ExprTry, FnArg,
+    Ident, ItemFn, ItemMacro, ItemMod, Stmt, StmtMacro,
 };
 
+// smoelius: Attributes that make a function a test. An attribute's arguments are not considered,
+// e.g., `#[tokio::test(flavor = "multi_thread")]` is treated like `#[tokio::test]`. Additional
+// attributes can be listed in necessist.toml's `test_attributes`.
+const TEST_ATTRIBUTES: &[&str] = &[
+    "actix_rt::test",
+    "actix_web::test",
+    "async_std::test",
+    "rstest",
+    "rstest::rstest",
+    "sqlx::test",
+    "test",
+    "test_case",
+    "test_case::test_case",
+    "test_case::test_matrix",
+    "test_log::test",
+    "test_matrix",
+    "tokio::test",
+    "wasm_bindgen_test",
+    "wasm_bindgen_test::wasm_bindgen_test",
+];
+
+// smoelius: Attributes that expand a test function into a module of the same name containing one
+// test per case.
+const PARAMETERIZED_TEST_ATTRIBUTES: &[&str] = &[
+    "test_case",
+    "test_case::test_case",
+    "test_case::test_matrix",
+    "test_matrix",
+];
+
+// smoelius: `#[rstest]` expands a function into a module only if the function has cases or
+// arguments with lists of values.
+const RSTEST_CASE_ATTRIBUTES: &[&str] = &["case", "rstest::case"];
+const RSTEST_ARGUMENT_ATTRIBUTES: &[&str] = &["files", "rstest::files", "rstest::values", "values"];
+
+// smoelius: Methods that are "transparent" when called on the result of a call, e.g., in
+// `client.connect().unwrap();`, the statement is handled as though it were `client.connect();`.
+// Additional methods can be listed in necessist.toml's `transparent_methods`.
+const TRANSPARENT_METHODS: &[&str] = &["expect", "expect_err", "unwrap", "unwrap_err"];
+
 #[cfg_attr(dylint_lib = "general", allow(non_local_effect_before_error_return))]
 pub(super) fn visit<'ast>(
     generic_visitor: GenericVisitor<'_, '_, '_, 'ast, Rust>,
     storage: &RefCell<Storage<'ast>>,
     file: &'ast File,
-) -> Result<Vec<Span>> {
+) -> Result<Vec<Candidate>> {
     let mut visitor = Visitor::new(generic_visitor, storage);
-    visitor.visit_file(file);
+    visitor.visit_file(&file.file);
+    match &file.doctests {
+        Some(Ok(doctests)) => {
+            storage.borrow_mut().doctests = Some(&doctests.doctests);
+            visitor.visit_file(&doctests.file);
+        }
+        Some(Err(error)) => {
+            source_warn(
+                visitor.generic_visitor.context,
+                Warning::DoctestParsingFailed,
+                &visitor.generic_visitor.source_file,
+                &format!("Failed to parse doctests; skipping them: {error}"),
+                WarnFlags::empty(),
+            )?;
+        }
+        None => {}
+    }
     if let Some(error) = storage.borrow_mut().error.take() {
         Err(error)
     } else {
@@ -50,13 +106,30 @@ impl<'context, 'config, 'framework, 'ast, 'storage>
 impl<'context, 'config, 'framework, 'ast, 'storage> Visit<'ast>
     for Visitor<'context, 'config, 'framework, 'ast, 'storage>
 {
+    fn visit_file(&mut self, file: &'ast syn::File) {
+        self.storage.borrow_mut().uses.push(Uses::new(&file.items));
+
+        visit_file(self, file);
+
+        self.storage.borrow_mut().uses.pop();
+    }
+
     fn visit_item_mod(&mut self, item: &'ast ItemMod) {
         if self.test_ident.is_none() {
             self.storage.borrow_mut().module_path.push(&item.ident);
         }
 
+        let items = item.content.as_ref().map(|(_, items)| items.as_slice());
+        if let Some(items) = items {
+            self.storage.borrow_mut().uses.push(Uses::new(items));
+        }
+
         visit_item_mod(self, item);
 
+        if items.is_some() {
+            self.storage.borrow_mut().uses.pop();
+        }
+
         if self.test_ident.is_none() {
             assert_eq!(
                 self.storage.borrow_mut().module_path.pop(),
@@ -66,10 +139,30 @@ impl<'context, 'config, 'framework, 'ast, 'storage> Visit<'ast>
     }
 
     fn visit_item_fn(&mut self, item: &'ast ItemFn) {
-        if let Some(ident) = is_test(item) {
+        // smoelius: While doctests are being visited, the only tests are their `main` functions.
+        let doctests = self.storage.borrow().doctests;
+        let doctest = doctests.and_then(|doctests| {
+            if self.test_ident.is_none() {
+                doctest_main(doctests, item)
+            } else {
+                None
+            }
+        });
+        let ident = if doctests.is_some() {
+            doctest.map(|_| &item.sig.ident)
+        } else {
+            is_test(self.generic_visitor.config, item)
+                .filter(|_| self.generic_visitor.framework.include_ignored || !is_ignored(item))
+        };
+
+        if let Some(ident) = ident {
             assert!(self.test_ident.is_none());
             self.test_ident = Some(ident);
 
+            self.storage.borrow_mut().parameterized_test = is_parameterized(item);
+            self.storage.borrow_mut().ignored_test = is_ignored(item);
+            self.storage.borrow_mut().doctest = doctest;
+
             let walk = self.generic_visitor.visit_test(self.storage, item);
 
             if walk {
@@ -80,6 +173,24 @@ impl<'context, 'config, 'framework, 'ast, 'storage> Visit<'ast>
 
             assert!(self.test_ident == Some(ident));
             self.test_ident = None;
+
+            self.storage.borrow_mut().doctest = None;
+        }
+    }
+
+    // smoelius: The tests in a test-generating macro, e.g., `proptest!`, are visited as though
+    // they appeared in place of the macro.
+    fn visit_item_macro(&mut self, item: &'ast ItemMacro) {
+        let storage = self.storage.borrow();
+        let tests = if storage.doctests.is_none() && self.test_ident.is_none() {
+            storage.macro_tests.get(&test_macros::key(item))
+        } else {
+            None
+        };
+        drop(storage);
+
+        for test in tests.into_iter().flatten() {
+            self.visit_item_fn(test);
         }
     }
 
@@ -141,8 +252,20 @@ impl<'context, 'config, 'framework, 'ast, 'storage> Visit<'ast>
     fn visit_expr_method_call(&mut self, method_call: &'ast ExprMethodCall) {
         let call = Call::MethodCall(method_call);
 
+        // smoelius: If the method is transparent, hold onto the call statement so that it is
+        // attributed to the receiver, which is visited next.
+        let call_statement = if is_transparent(self.generic_visitor.config, method_call) {
+            self.generic_visitor.call_statement.take()
+        } else {
+            None
+        };
+
         let walk = self.generic_visitor.visit_call(self.storage, call);
 
+        if call_statement.is_some() {
+            self.generic_visitor.call_statement = call_statement;
+        }
+
         if walk {
             visit_expr_method_call(self, method_call);
         } else {
@@ -153,27 +276,10 @@ impl<'context, 'config, 'framework, 'ast, 'storage> Visit<'ast>
     }
 }
 
-fn is_test(item: &ItemFn) -> Option<&Ident> {
+fn is_test<'ast>(config: &config::Compiled, item: &'ast ItemFn) -> Option<&'ast Ident> {
     if item.attrs.iter().any(|attr| {
-        let path = attr
-            .path()
-            .segments
-            .iter()
-            .map(|PathSegment { ident, arguments }| {
-                if arguments.is_empty() {
-                    Some(ident.to_string())
-                } else {
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
-        matches!(
-            path.iter()
-                .map(Option::as_deref)
-                .collect::<Vec<_>>()
-                .as_slice(),
-            &[Some("test")] | &[Some("tokio"), Some("test")]
-        )
+        let path = attr_path(attr);
+        TEST_ATTRIBUTES.contains(&path.as_str()) || config.is_test_attribute(&path)
     }) {
         Some(&item.sig.ident)
     } else {
@@ -181,13 +287,85 @@ fn is_test(item: &ItemFn) -> Option<&Ident> {
     }
 }
 
+/// Returns the doctest whose `main` function is `item`, if any
+fn doctest_main<'ast>(doctests: &'ast [Doctest], item: &ItemFn) -> Option<&'ast Doctest> {
+    if item.sig.ident != "main" {
+        return None;
+    }
+    let line = item.sig.ident.span().start().line;
+    doctests
+        .iter()
+        .find(|doctest| doctest.lines.contains(&line))
+}
+
+fn is_transparent(config: &config::Compiled, method_call: &ExprMethodCall) -> bool {
+    let method = method_call.method.to_string();
+    if !TRANSPARENT_METHODS.contains(&method.as_str()) && !config.is_transparent_method(&method) {
+        return false;
+    }
+
+    let mut receiver = &*method_call.receiver;
+    loop {
+        match receiver {
+            Expr::Await(ExprAwait { base: expr, .. })
+            | Expr::Paren(ExprParen { expr, .. })
+            | Expr::Try(ExprTry { expr, .. }) => receiver = expr,
+            Expr::Call(_) | Expr::Macro(_) | Expr::MethodCall(_) => return true,
+            _ => return false,
+        }
+    }
+}
+
+fn is_parameterized(item: &ItemFn) -> bool {
+    let has_attr = |attrs: &[Attribute], paths: &[&str]| {
+        attrs
+            .iter()
+            .any(|attr| paths.contains(&attr_path(attr).as_str()))
+    };
+
+    has_attr(&item.attrs, PARAMETERIZED_TEST_ATTRIBUTES)
+        || has_attr(&item.attrs, RSTEST_CASE_ATTRIBUTES)
+        || item.sig.inputs.iter().any(|input| match input {
+            FnArg::Typed(pat_type) => has_attr(&pat_type.attrs, RSTEST_ARGUMENT_ATTRIBUTES),
+            FnArg::Receiver(_) => false,
+        })
+}
+
+fn is_ignored(item: &ItemFn) -> bool {
+    item.attrs.iter().any(|attr| attr_path(attr) == "ignore")
+}
+
+pub(super) fn is_should_panic(item: &ItemFn) -> bool {
+    item.attrs
+        .iter()
+        .any(|attr| attr_path(attr) == "should_panic")
+}
+
+/// Returns an attribute's path without its arguments, e.g., `tokio::test` for
+/// `#[tokio::test(flavor = "multi_thread")]`
+fn attr_path(attr: &Attribute) -> String {
+    attr.path()
+        .segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
 #[cfg(test)]
 mod test {
-    use super::Rust;
+    use super::{
+        is_ignored, is_parameterized, is_should_panic, is_test, is_transparent, Rust,
+        TEST_ATTRIBUTES, TRANSPARENT_METHODS,
+    };
     use crate::ParseLow;
     use if_chain::if_chain;
+    use necessist_core::config;
     use std::fs::read_to_string;
-    use syn::{parse_file, Expr, ExprArray, ExprLit, ExprReference, Item, ItemConst, Lit};
+    use syn::{
+        parse_file, parse_quote, Expr, ExprArray, ExprLit, ExprMethodCall, ExprReference, Item,
+        ItemConst, ItemFn, Lit,
+    };
 
     const UNNECESSARY_CONVERSION_FOR_TRAIT_URL: &str = "https://raw.githubusercontent.com/trailofbits/dylint/master/examples/supplementary/unnecessary_conversion_for_trait/src/lib.rs";
 
@@ -205,6 +383,111 @@ mod test {
         "unwrap_err",
     ];
 
+    #[test]
+    fn test_attributes_are_recognized() {
+        let config = config::Toml {
+            test_attributes: vec!["my_crate::my_test".to_owned()],
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        let tests: [ItemFn; 4] = [
+            parse_quote! { #[test] fn f() {} },
+            parse_quote! { #[tokio::test(flavor = "multi_thread")] async fn f() {} },
+            parse_quote! { #[serial] #[test_log::test] fn f() {} },
+            parse_quote! { #[my_crate::my_test] fn f() {} },
+        ];
+        for item in &tests {
+            assert!(is_test(&config, item).is_some());
+        }
+
+        let non_test: ItemFn = parse_quote! { #[my_test] fn f() {} };
+        assert!(is_test(&config, &non_test).is_none());
+    }
+
+    #[test]
+    fn parameterized_tests_are_recognized() {
+        let parameterized: [ItemFn; 3] = [
+            parse_quote! { #[test_case(1 ; "one")] fn f(x: u32) {} },
+            parse_quote! { #[rstest] #[case(1)] fn f(#[case] x: u32) {} },
+            parse_quote! { #[rstest] fn f(#[values(1, 2)] x: u32) {} },
+        ];
+        for item in &parameterized {
+            assert!(is_parameterized(item));
+        }
+
+        let not_parameterized: [ItemFn; 2] = [
+            parse_quote! { #[test] fn f() {} },
+            parse_quote! { #[rstest] fn f(fixture: u32) {} },
+        ];
+        for item in &not_parameterized {
+            assert!(!is_parameterized(item));
+        }
+    }
+
+    #[test]
+    fn ignored_and_should_panic_tests_are_recognized() {
+        let item: ItemFn = parse_quote! { #[test] #[ignore = "slow"] #[should_panic] fn f() {} };
+        assert!(is_ignored(&item));
+        assert!(is_should_panic(&item));
+
+        let item: ItemFn = parse_quote! { #[test] #[should_panic(expected = "oops")] fn f() {} };
+        assert!(!is_ignored(&item));
+        assert!(is_should_panic(&item));
+
+        let item: ItemFn = parse_quote! { #[test] fn f() {} };
+        assert!(!is_ignored(&item));
+        assert!(!is_should_panic(&item));
+    }
+
+    #[test]
+    fn transparent_methods_are_recognized() {
+        let config = config::Toml {
+            transparent_methods: vec!["unwrap_or_default".to_owned()],
+            ..Default::default()
+        }
+        .compile()
+        .unwrap();
+
+        let transparent: [ExprMethodCall; 4] = [
+            parse_quote! { client.connect().unwrap() },
+            parse_quote! { connect().await.expect("failed to connect") },
+            parse_quote! { connect!()?.unwrap_err() },
+            parse_quote! { client.connect().unwrap_or_default() },
+        ];
+        for method_call in &transparent {
+            assert!(is_transparent(&config, method_call));
+        }
+
+        let opaque: [ExprMethodCall; 2] = [
+            parse_quote! { client.unwrap() },
+            parse_quote! { client.connect().is_ok() },
+        ];
+        for method_call in &opaque {
+            assert!(!is_transparent(&config, method_call));
+        }
+    }
+
+    #[test]
+    fn readme_contains_test_attributes() {
+        assert!(readme_contains_code_bulleted_list(TEST_ATTRIBUTES));
+    }
+
+    #[cfg_attr(
+        dylint_lib = "assert_eq_arg_misordering",
+        allow(assert_eq_arg_misordering)
+    )]
+    #[test]
+    fn test_attributes_are_sorted() {
+        assert_eq!(sort(TEST_ATTRIBUTES), TEST_ATTRIBUTES);
+    }
+
+    #[test]
+    fn readme_contains_transparent_methods() {
+        assert!(readme_contains_code_bulleted_list(TRANSPARENT_METHODS));
+    }
+
     #[test]
     fn readme_contains_ignored_macros() {
         assert!(readme_contains_code_bulleted_list(
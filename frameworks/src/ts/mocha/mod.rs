@@ -6,8 +6,8 @@ use anyhow::{anyhow, Result};
 use if_chain::if_chain;
 use log::debug;
 use necessist_core::{
-    framework::Postprocess, source_warn, LightContext, LineColumn, SourceFile, Span, WarnFlags,
-    Warning,
+    framework::Postprocess, source_warn, Candidate, LightContext, LineColumn, SourceFile, Span,
+    WarnFlags, Warning,
 };
 use once_cell::sync::Lazy;
 use regex::Regex;
@@ -300,7 +300,7 @@ impl ParseLow for Mocha {
         generic_visitor: GenericVisitor<'_, '_, '_, 'ast, Self>,
         storage: &RefCell<<Self::Types as AbstractTypes>::Storage<'ast>>,
         file: &'ast <Self::Types as AbstractTypes>::File,
-    ) -> Result<Vec<Span>> {
+    ) -> Result<Vec<Candidate>> {
         visit(generic_visitor, storage, &file.1)
     }
 
@@ -139,7 +139,8 @@ fn hack_feature_powerset_udeps() {
 
 #[test]
 fn license() {
-    let re = Regex::new(r"^[^:]*\b(Apache-2.0|0BSD|BSD-\d-Clause|CC0-1.0|MIT|MPL-2\.0)\b").unwrap();
+    let re =
+        Regex::new(r"^[^:]*\b(Apache-2.0|0BSD|BSD-\d-Clause|CC0-1.0|ISC|MIT|MPL-2\.0)\b").unwrap();
 
     for line in std::str::from_utf8(
         &Command::new("cargo")
@@ -1,9 +1,21 @@
 use assert_cmd::prelude::*;
 use fs_extra::dir::{copy, CopyOptions};
-use necessist_core::util;
+use necessist_core::{
+    framework::Auto, necessist, necessist_with_observer, util, Candidate, Necessist, Observer,
+    Outcome, Removal,
+};
+use necessist_frameworks::Identifier;
 use predicates::prelude::*;
-use std::{path::PathBuf, process::Command};
-use tempfile::tempdir;
+use std::{
+    cell::RefCell,
+    fs::{read_dir, read_to_string, rename, write},
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    process::{Command, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+use tempfile::{tempdir, TempDir};
 
 const ROOT: &str = "../examples/basic";
 const TIMEOUT: &str = "5";
@@ -20,6 +32,18 @@ fn necessist_db_can_be_moved() {
 
     let _remove_file = util::RemoveFile(necessist_db);
 
+    let tempdir = copy_root();
+
+    Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["--root", &tempdir.path().to_string_lossy(), "--resume"])
+        .assert()
+        .success()
+        .stdout(predicate::eq("4 candidates in 1 test file\n"));
+}
+
+/// Copies the `basic` example to a temporary directory, so that a test can modify it
+fn copy_root() -> TempDir {
     let tempdir = tempdir().unwrap();
 
     copy(
@@ -32,10 +56,341 @@ fn necessist_db_can_be_moved() {
     )
     .unwrap();
 
+    tempdir
+}
+
+#[test]
+fn resume_remaps_removals_of_moved_candidates() {
+    let tempdir = copy_root();
+
+    let root = tempdir.path().to_string_lossy().to_string();
+    let lib_rs = tempdir.path().join("src/lib.rs");
+
     Command::cargo_bin("necessist")
         .unwrap()
-        .args(["--root", &tempdir.path().to_string_lossy(), "--resume"])
+        .args(["--root", &root, "--timeout", TIMEOUT])
+        .assert()
+        .success();
+
+    let contents = read_to_string(&lib_rs).unwrap();
+    write(
+        &lib_rs,
+        contents.replacen("#[test]\nfn failed", "\n#[test]\nfn failed", 1),
+    )
+    .unwrap();
+
+    // smoelius: Had any candidate been re-run, its file would have been dry run and mutilated.
+    Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["--root", &root, "--timeout", TIMEOUT, "--resume"])
         .assert()
         .success()
-        .stdout(predicate::eq("4 candidates in 1 test file\n"));
+        .stdout(predicate::eq("4 candidates in 1 test file\n"))
+        .stderr(predicate::str::is_empty());
+
+    Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["--root", &root, "--dump", "--verbose"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "src/lib.rs:22:5-22:12: `n += 1;` failed",
+        ));
+}
+
+#[test]
+fn observer_receives_events() {
+    #[derive(Default)]
+    struct Events {
+        n_candidates: RefCell<usize>,
+        n_removals_started: RefCell<usize>,
+        n_removals_finished: RefCell<usize>,
+        outcomes: RefCell<Vec<Outcome>>,
+    }
+
+    impl Observer for Events {
+        fn on_candidates(&self, candidates: &[Candidate]) {
+            *self.n_candidates.borrow_mut() = candidates.len();
+        }
+        fn on_removal_started(&self, _candidate: &Candidate) {
+            *self.n_removals_started.borrow_mut() += 1;
+        }
+        fn on_removal_finished(&self, _candidate: &Candidate, removal: Option<&Removal>) {
+            *self.n_removals_finished.borrow_mut() += 1;
+            if let Some(removal) = removal {
+                self.outcomes.borrow_mut().push(removal.outcome);
+            }
+        }
+    }
+
+    let tempdir = copy_root();
+
+    let opts = Necessist {
+        no_sqlite: true,
+        quiet: true,
+        root: Some(tempdir.path().to_path_buf()),
+        timeout: Some(TIMEOUT.parse().unwrap()),
+        ..Default::default()
+    };
+
+    let events = Events::default();
+
+    necessist_with_observer(&opts, Auto::<Identifier>::default(), &events).unwrap();
+
+    assert_eq!(4, *events.n_candidates.borrow());
+    assert_eq!(4, *events.n_removals_started.borrow());
+    assert_eq!(4, *events.n_removals_finished.borrow());
+    assert_eq!(
+        vec![
+            Outcome::Passed,
+            Outcome::TimedOut,
+            Outcome::Failed,
+            Outcome::Nonbuildable
+        ],
+        *events.outcomes.borrow()
+    );
+}
+
+#[test]
+fn sessions_are_independent() {
+    for _ in 0..2 {
+        let tempdir = copy_root();
+
+        let opts = Necessist {
+            no_sqlite: true,
+            quiet: true,
+            root: Some(tempdir.path().to_path_buf()),
+            timeout: Some(TIMEOUT.parse().unwrap()),
+            ..Default::default()
+        };
+
+        necessist(&opts, Auto::<Identifier>::default()).unwrap();
+    }
+}
+
+#[test]
+fn interrupted_run_can_be_restored() {
+    let tempdir = copy_root();
+
+    let root = tempdir.path().to_string_lossy().to_string();
+    let lib_rs = tempdir.path().join("src/lib.rs");
+    let journal = tempdir.path().join("necessist.journal");
+
+    let original = read_to_string(&lib_rs).unwrap();
+
+    let mut child = Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["--root", &root, "--no-sqlite", "--timeout", TIMEOUT])
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+
+    // smoelius: The journal is written before the file is modified. So wait for the file's contents
+    // to change, not just for the journal to appear.
+    while !journal.try_exists().unwrap() || original == read_to_string(&lib_rs).unwrap() {
+        sleep(Duration::from_millis(10));
+    }
+
+    // smoelius: `Child::kill` sends SIGKILL, so `Backup`'s `Drop` does not run.
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    assert_ne!(original, read_to_string(&lib_rs).unwrap());
+
+    Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["--root", &root, "--no-sqlite"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("necessist restore"));
+
+    Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["restore", "--root", &root])
+        .assert()
+        .success();
+
+    assert_eq!(original, read_to_string(&lib_rs).unwrap());
+    assert!(!journal.try_exists().unwrap());
+    assert_eq!(1, read_dir(tempdir.path().join("src")).unwrap().count());
+}
+
+#[test]
+fn watch_rechecks_edited_test_file() {
+    let tempdir = copy_root();
+
+    let root = tempdir.path().to_string_lossy().to_string();
+    let lib_rs = tempdir.path().join("src/lib.rs");
+
+    // smoelius: Move the `failed` test before the `passed` test, so that trying the changed
+    // candidates in source order would not try the previously passed one first.
+    const FAILED: &str =
+        "#[test]\nfn failed() {\n    let mut n = 0;\n    n += 1;\n    assert!(n >= 1);\n}\n\n";
+    let contents = read_to_string(&lib_rs).unwrap();
+    write(
+        &lib_rs,
+        format!("{FAILED}{}", contents.replacen(FAILED, "", 1)),
+    )
+    .unwrap();
+
+    let mut child = Command::cargo_bin("necessist")
+        .unwrap()
+        .args([
+            "--root",
+            &root,
+            "--no-sqlite",
+            "--timeout",
+            TIMEOUT,
+            "--verbose",
+            "--watch",
+        ])
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+
+    let mut wait_for = |needle: &str| {
+        for line in &mut lines {
+            if line.unwrap().contains(needle) {
+                return;
+            }
+        }
+        panic!("`{needle}` not found");
+    };
+
+    wait_for("Watching for changes");
+
+    let contents = read_to_string(&lib_rs).unwrap();
+    write(
+        &lib_rs,
+        contents
+            .replacen("n += 1;\n    noop();", "n += 2;\n    noop();", 1)
+            .replacen("n += 1;\n    assert!", "n += 2;\n    assert!", 1),
+    )
+    .unwrap();
+
+    wait_for("re-checking 2 candidates (2 unchanged)");
+
+    // smoelius: The previously passed candidate is tried first.
+    let mut outcomes = Vec::new();
+    while outcomes.len() < 2 {
+        let line = lines.next().unwrap().unwrap();
+        if line.contains("`n += 2;`") {
+            outcomes.push(line.rsplit_once(' ').unwrap().1.to_owned());
+        }
+    }
+
+    // smoelius: Unlike `Child::kill`, SIGINT lets Necessist finish the current removal, i.e., kill
+    // the tests and restore the file.
+    Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .assert()
+        .success();
+    assert!(child.wait().unwrap().success());
+
+    assert_eq!(vec!["passed", "failed"], outcomes);
+}
+
+#[test]
+fn sampling_with_a_seed_is_reproducible() {
+    let tempdir = copy_root();
+
+    let root = tempdir.path().to_string_lossy().to_string();
+
+    let spans = (0..2)
+        .map(|_| {
+            let assert = Command::cargo_bin("necessist")
+                .unwrap()
+                .args(["--root", &root, "--no-sqlite", "--dump-candidates"])
+                .args(["--sample", "2", "--seed", "7"])
+                .assert()
+                .success()
+                .stdout(predicate::str::starts_with(
+                    "Sampling 2 of 4 candidates (seed 7)\n",
+                ));
+            let stdout = std::str::from_utf8(&assert.get_output().stdout).unwrap();
+            stdout
+                .lines()
+                .skip(1)
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(2, spans[0].len());
+    assert_eq!(spans[0], spans[1]);
+}
+
+#[test]
+fn vacuous_warns_about_sampled_runs() {
+    let tempdir = copy_root();
+
+    let root = tempdir.path().to_string_lossy().to_string();
+
+    Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["--root", &root, "--timeout", TIMEOUT])
+        .args(["--sample", "1", "--seed", "7"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["vacuous", "--root", &root])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "used --sample, so it may not have tried",
+        ));
+}
+
+#[test]
+fn shards_merge_into_one_run() {
+    let tempdir = copy_root();
+
+    let root = tempdir.path().to_string_lossy().to_string();
+
+    for i in 1..=2 {
+        Command::cargo_bin("necessist")
+            .unwrap()
+            .args(["--root", &root, "--timeout", TIMEOUT])
+            .args(["--shard", &format!("{i}/2"), "--shard-by", "span"])
+            .assert()
+            .success()
+            .stdout(predicate::str::starts_with(format!(
+                "2 candidates in 1 test file (shard {i}/2)\n"
+            )));
+
+        rename(
+            tempdir.path().join("necessist.db"),
+            tempdir.path().join(format!("{i}.db")),
+        )
+        .unwrap();
+    }
+
+    Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["merge", "1.db", "2.db", "-o", "necessist.db"])
+        .current_dir(&tempdir)
+        .assert()
+        .success();
+
+    let assert = Command::cargo_bin("necessist")
+        .unwrap()
+        .args(["--root", &root, "--dump", "--verbose"])
+        .assert()
+        .success();
+
+    let stdout = std::str::from_utf8(&assert.get_output().stdout).unwrap();
+
+    let outcomes = stdout
+        .lines()
+        .map(|line| line.rsplit_once(' ').unwrap().1)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec!["passed", "timed-out", "failed", "nonbuildable"],
+        outcomes
+    );
 }
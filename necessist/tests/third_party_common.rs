@@ -1,5 +1,5 @@
 use assert_cmd::output::OutputError;
-use necessist_core::{util, Span};
+use necessist_core::{util, LightContext, Necessist, Session, Span};
 use regex::Regex;
 use serde::Deserialize;
 use similar_asserts::SimpleDiff;
@@ -511,6 +511,16 @@ fn run_test(tempdir: &Path, path: &Path, test: &Test) -> String {
 fn check_sqlite_urls(tempdir: &Path, root: &Path, test: &Test) {
     let root = Rc::new(root.to_path_buf());
 
+    let opts = Necessist::default();
+    let session = Session::default();
+    let context = LightContext {
+        opts: &opts,
+        root: &root,
+        println: &|_| {},
+        observer: &(),
+        session: &session,
+    };
+
     let necessist_db = root.join("necessist.db");
 
     let url_https = if let Some(suffix) = test.url.strip_prefix(PREFIX_SSH) {
@@ -531,7 +541,7 @@ fn check_sqlite_urls(tempdir: &Path, root: &Path, test: &Test) {
 
     for line in stdout.lines() {
         let (s, url) = line.split_once('|').unwrap();
-        let span = Span::parse(&root, s).unwrap();
+        let span = Span::parse(&context, s).unwrap();
         assert_eq!(
             &format!(
                 "{}/blob/{}/{}#L{}-L{}",